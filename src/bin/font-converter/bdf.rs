@@ -0,0 +1,257 @@
+//! BDF (Glyph Bitmap Distribution Format) font import
+//!
+//! See https://www.x.org/docs/BDF/bdf.pdf for the format specification.
+//!
+//! Copyright 2019 Ryan Kurte
+
+use std::convert::TryFrom;
+
+use super::subset::Charset;
+use super::{bitmap_char, metric, Char, Font};
+
+/// Bounding box in BDF form (width, height, x offset, y offset of the bottom edge)
+type BoundingBox = (i32, i32, i32, i32);
+
+/// Parse a fixed number of integer arguments from a BDF line
+fn numbers<'a, I>(line: usize, args: I, count: usize) -> Result<Vec<i32>, String>
+where
+    I: Iterator<Item = &'a str>,
+{
+    let values = args
+        .take(count)
+        .map(|a| a.parse::<i32>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("line {}: {}", line, e))?;
+
+    if values.len() != count {
+        return Err(format!("line {}: expected {} values", line, count));
+    }
+
+    Ok(values)
+}
+
+/// Parse a single character, from the line following STARTCHAR to ENDCHAR
+///
+/// Returns None for glyphs with no standard encoding.
+fn parse_char<'a, I>(lines: &mut I, font_bbox: Option<BoundingBox>) -> Result<Option<Char>, String>
+where
+    I: Iterator<Item = (usize, &'a str)>,
+{
+    let mut encoding = None;
+    let mut advance = None;
+    let mut bbox = font_bbox;
+
+    // Parse character attributes
+    loop {
+        let (n, line) = lines.next().ok_or("unexpected end of file in STARTCHAR")?;
+        let mut parts = line.split_whitespace();
+
+        match parts.next() {
+            Some("ENCODING") => encoding = Some(numbers(n, parts, 1)?[0]),
+            Some("DWIDTH") => advance = Some(numbers(n, parts, 2)?[0]),
+            Some("BBX") => {
+                let v = numbers(n, parts, 4)?;
+                bbox = Some((v[0], v[1], v[2], v[3]));
+            }
+            Some("BITMAP") => break,
+            Some("ENDCHAR") => return Err(format!("line {}: ENDCHAR without BITMAP", n)),
+            _ => (),
+        }
+    }
+
+    let (width, height, x_off, y_off) = bbox.ok_or("missing BBX and FONTBOUNDINGBOX")?;
+    let stride = (width.max(0) as usize).div_ceil(8);
+
+    // Parse bitmap rows
+    let mut rows = Vec::with_capacity(height.max(0) as usize);
+    loop {
+        let (n, line) = lines.next().ok_or("unexpected end of file in BITMAP")?;
+        let line = line.trim();
+
+        if line == "ENDCHAR" {
+            break;
+        }
+
+        let row = (0..stride)
+            .map(|i| {
+                line.get(i * 2..i * 2 + 2)
+                    .and_then(|b| u8::from_str_radix(b, 16).ok())
+                    .ok_or_else(|| format!("line {}: invalid bitmap row '{}'", n, line))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        rows.push(row);
+    }
+
+    if rows.len() != height as usize {
+        return Err(format!(
+            "expected {} bitmap rows for character {:?}, found {}",
+            height,
+            encoding,
+            rows.len()
+        ));
+    }
+
+    // Skip characters without a standard encoding
+    let code = match encoding
+        .and_then(|e| u32::try_from(e).ok())
+        .and_then(std::char::from_u32)
+    {
+        Some(c) => c,
+        None => return Ok(None),
+    };
+
+    let advance = advance.unwrap_or(width);
+
+    let c = bitmap_char(
+        code,
+        width,
+        height,
+        advance,
+        x_off,
+        y_off + height,
+        |x, y| rows[y][x / 8] & (0x80 >> (x % 8)) != 0,
+    )?;
+
+    Ok(Some(c))
+}
+
+/// Load a BDF font, retaining characters in the provided set
+pub fn load(text: &str, charset: &Charset) -> Result<Font, String> {
+    let mut lines = text.lines().enumerate().map(|(n, l)| (n + 1, l));
+
+    let mut bbox = None;
+    let mut pixel_size = None;
+    let mut ascent = None;
+    let mut descent = None;
+    let mut chars = Vec::new();
+
+    while let Some((n, line)) = lines.next() {
+        let mut parts = line.split_whitespace();
+
+        match parts.next() {
+            Some("FONTBOUNDINGBOX") => {
+                let v = numbers(n, parts, 4)?;
+                bbox = Some((v[0], v[1], v[2], v[3]));
+            }
+            Some("PIXEL_SIZE") => pixel_size = Some(numbers(n, parts, 1)?[0]),
+            Some("FONT_ASCENT") => ascent = Some(numbers(n, parts, 1)?[0]),
+            Some("FONT_DESCENT") => descent = Some(numbers(n, parts, 1)?[0]),
+            Some("STARTCHAR") => {
+                let c = parse_char(&mut lines, bbox)?;
+                match c {
+                    Some(c) if charset.contains(c.code) => chars.push(c),
+                    _ => (),
+                }
+            }
+            _ => (),
+        }
+    }
+
+    // Fall back to the font bounding box where ascent and descent are not specified
+    let (ascent, descent) = match (ascent, descent, bbox) {
+        (Some(a), Some(d), _) => (a, d),
+        (_, _, Some((_w, h, _x, y))) => (h + y, -y),
+        _ => return Err("missing FONT_ASCENT / FONT_DESCENT and FONTBOUNDINGBOX".into()),
+    };
+
+    let height = ascent + descent;
+
+    Ok(Font {
+        size: metric("size", ' ', pixel_size.unwrap_or(height))?,
+        height: metric("line height", ' ', height)?,
+        ascent: metric("ascent", ' ', ascent)?,
+        chars,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FONT: &str = "STARTFONT 2.1
+FONT -test-fixed-medium-r-normal--6-60-75-75-c-40-iso10646-1
+SIZE 6 75 75
+FONTBOUNDINGBOX 4 6 0 -1
+STARTPROPERTIES 3
+PIXEL_SIZE 6
+FONT_ASCENT 5
+FONT_DESCENT 1
+ENDPROPERTIES
+CHARS 3
+STARTCHAR B
+ENCODING 66
+SWIDTH 640 0
+DWIDTH 4 0
+BBX 3 5 0 0
+BITMAP
+C0
+A0
+C0
+A0
+C0
+ENDCHAR
+STARTCHAR A
+ENCODING 65
+SWIDTH 640 0
+DWIDTH 4 0
+BBX 3 4 0 0
+BITMAP
+40
+A0
+E0
+A0
+ENDCHAR
+STARTCHAR unencoded
+ENCODING -1
+DWIDTH 4 0
+BBX 1 1 0 0
+BITMAP
+80
+ENDCHAR
+ENDFONT
+";
+
+    const ASCII: Charset = Charset::Range(32, 127);
+
+    #[test]
+    fn test_load_bdf() {
+        let font = load(FONT, &ASCII).unwrap();
+
+        assert_eq!(font.size, 6);
+        assert_eq!(font.height, 6);
+        assert_eq!(font.ascent, 5);
+        assert_eq!(font.chars.len(), 2);
+
+        let b = &font.chars[0];
+        assert_eq!(b.code, 'B');
+        assert_eq!((b.width, b.height, b.advance), (3, 5, 4));
+        assert_eq!((b.bearing_x, b.bearing_y), (0, 5));
+        assert_eq!(b.data, vec![0xC0, 0xA0, 0xC0, 0xA0, 0xC0]);
+
+        let a = &font.chars[1];
+        assert_eq!(a.code, 'A');
+        assert_eq!((a.width, a.height, a.bearing_y), (3, 4, 4));
+        assert_eq!(a.data, vec![0x40, 0xA0, 0xE0, 0xA0]);
+    }
+
+    #[test]
+    fn test_load_bdf_range() {
+        let font = load(FONT, &Charset::Range('B' as u32, 127)).unwrap();
+        assert_eq!(font.chars.len(), 1);
+        assert_eq!(font.chars[0].code, 'B');
+
+        let font = load(FONT, &Charset::Subset("AC".chars().collect())).unwrap();
+        assert_eq!(font.chars.len(), 1);
+        assert_eq!(font.chars[0].code, 'A');
+    }
+
+    #[test]
+    fn test_load_bdf_invalid() {
+        let truncated = &FONT[..FONT.find("ENDCHAR").unwrap()];
+        assert!(load(truncated, &ASCII).is_err());
+
+        let invalid = FONT.replace("BBX 3 5 0 0", "BBX 3 6 0 0");
+        assert!(load(&invalid, &ASCII).is_err());
+    }
+}
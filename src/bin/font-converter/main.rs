@@ -0,0 +1,422 @@
+//! Font converter renders TTF/OTF fonts, or imports BDF/PCF bitmap fonts,
+//! into micro-gui bitmap fonts (as rust source or binary font files)
+//!
+//! Copyright 2019 Ryan Kurte
+
+use std::convert::TryFrom;
+use std::fmt::Write as FmtWrite;
+use std::fs::{self, File};
+use std::io::prelude::*;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use micro_gui::fonts::{self, binary};
+
+extern crate rusttype;
+
+extern crate structopt;
+use structopt::StructOpt;
+
+mod bdf;
+mod pcf;
+mod preview;
+mod subset;
+mod ttf;
+
+use subset::Charset;
+
+#[derive(Debug, Clone, StructOpt)]
+pub struct Config {
+    #[structopt(
+        short = "f",
+        long = "font",
+        default_value = "./fonts/SourceCodePro-Regular.ttf"
+    )]
+    /// Font to be converted (TTF/OTF, BDF, or PCF, selected by file extension)
+    pub font: String,
+
+    #[structopt(short = "s", long = "size", default_value = "12")]
+    /// Font render size (in pixels per em), ignored for bitmap fonts
+    pub size: u16,
+
+    #[structopt(short = "o", long = "output", default_value = "./output.rs")]
+    /// Output font file
+    pub output: String,
+
+    #[structopt(long = "format", default_value = "rust")]
+    /// Output format (rust or binary)
+    pub format: Format,
+
+    #[structopt(short = "n", long = "name")]
+    /// Name for the generated font object (defaults to one derived from the font file)
+    pub name: Option<String>,
+
+    #[structopt(long = "start-char", default_value = "32")]
+    /// Start character code
+    pub start: u32,
+    #[structopt(long = "end-char", default_value = "127")]
+    /// End character code (exclusive)
+    pub end: u32,
+
+    #[structopt(long = "subset")]
+    /// Text files to subset the font to, only characters used in these files are converted
+    /// (replaces the start and end character range)
+    pub subset: Vec<String>,
+
+    #[structopt(long = "subset-strings")]
+    /// String table files (rust/C source, JSON, gettext etc.) to subset the font to,
+    /// only characters in double quoted string literals are converted
+    pub subset_strings: Vec<String>,
+
+    #[structopt(long = "threshold", default_value = "0.35")]
+    /// Coverage threshold (0.0 to 1.0) above which a pixel is considered set, ignored for bitmap fonts
+    pub threshold: f32,
+
+    #[structopt(long = "preview")]
+    /// Write a preview sheet of the converted font (PNG or PBM, selected by file extension)
+    pub preview: Option<String>,
+
+    #[structopt(
+        long = "preview-text",
+        default_value = "The quick brown fox jumps over the lazy dog\\n0123456789"
+    )]
+    /// Sample text to render in the preview sheet
+    pub preview_text: String,
+
+    #[structopt(long = "preview-scale", default_value = "1")]
+    /// Integer scale factor for the preview sheet
+    pub preview_scale: usize,
+}
+
+/// Output format for converted fonts
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    /// Rust source defining a static `Font`
+    Rust,
+    /// Binary font, loaded at runtime with `BinaryFont::parse`
+    Binary,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rust" => Ok(Format::Rust),
+            "binary" => Ok(Format::Binary),
+            _ => Err(format!("Unsupported output format: {}", s)),
+        }
+    }
+}
+
+/// Font rendered into bitmap form
+pub struct Font {
+    pub size: u8,
+    pub height: u8,
+    pub ascent: u8,
+    pub chars: Vec<Char>,
+}
+
+/// Character rendered into a packed bitmap
+pub struct Char {
+    pub code: char,
+    pub width: u8,
+    pub height: u8,
+    pub advance: u8,
+    pub bearing_x: i8,
+    pub bearing_y: i8,
+    pub data: Vec<u8>,
+}
+
+impl Char {
+    /// Borrow the rendered character as a micro-gui character
+    pub fn as_char(&self) -> fonts::Char<'_> {
+        fonts::Char {
+            code: self.code,
+            width: self.width,
+            height: self.height,
+            advance: self.advance,
+            bearing_x: self.bearing_x,
+            bearing_y: self.bearing_y,
+            data: &self.data,
+        }
+    }
+}
+
+/// Convert a metric into the provided type, failing if it is out of range
+pub fn metric<T: TryFrom<i32>>(name: &str, code: char, value: i32) -> Result<T, String> {
+    T::try_from(value).map_err(|_| {
+        format!(
+            "Character {:?} {} ({}) out of range, try a smaller font size",
+            code, name, value
+        )
+    })
+}
+
+/// Build a character from bitmap font metrics and a pixel accessor, preserving the
+/// source bounding box
+pub fn bitmap_char<F>(
+    code: char,
+    width: i32,
+    height: i32,
+    advance: i32,
+    bearing_x: i32,
+    bearing_y: i32,
+    pixel: F,
+) -> Result<Char, String>
+where
+    F: Fn(usize, usize) -> bool,
+{
+    let (w, h) = (width.max(0) as usize, height.max(0) as usize);
+    let stride = w.div_ceil(8);
+    let mut data = vec![0u8; stride * h];
+
+    for y in 0..h {
+        for x in 0..w {
+            if pixel(x, y) {
+                data[y * stride + x / 8] |= 0x80 >> (x % 8);
+            }
+        }
+    }
+
+    Ok(Char {
+        code,
+        width: metric("width", code, width)?,
+        height: metric("height", code, height)?,
+        advance: metric("advance", code, advance)?,
+        bearing_x: metric("x bearing", code, bearing_x)?,
+        bearing_y: metric("y bearing", code, bearing_y)?,
+        data,
+    })
+}
+
+/// Maximum line and array widths used by rustfmt, used to lay out data arrays
+const MAX_WIDTH: usize = 100;
+const MAX_ARRAY_WIDTH: usize = 60;
+
+/// Generate rust source for a rendered font
+fn generate(name: &str, source: &str, font: &Font) -> String {
+    let mut s = String::new();
+
+    writeln!(s, "//! {} {}px bitmap font", source, font.size).unwrap();
+    writeln!(s, "//!").unwrap();
+    writeln!(s, "//! Generated by micro-gui font-converter").unwrap();
+    writeln!(s).unwrap();
+    writeln!(s, "use micro_gui::fonts::{{Char, Font}};").unwrap();
+    writeln!(s).unwrap();
+    writeln!(s, "pub static {}: Font = Font {{", name).unwrap();
+    writeln!(s, "    size: {},", font.size).unwrap();
+    writeln!(s, "    height: {},", font.height).unwrap();
+    writeln!(s, "    ascent: {},", font.ascent).unwrap();
+    writeln!(s, "    chars: &[").unwrap();
+
+    for c in &font.chars {
+        writeln!(s, "        Char {{").unwrap();
+        writeln!(s, "            code: {:?},", c.code).unwrap();
+        writeln!(s, "            width: {},", c.width).unwrap();
+        writeln!(s, "            height: {},", c.height).unwrap();
+        writeln!(s, "            advance: {},", c.advance).unwrap();
+        writeln!(s, "            bearing_x: {},", c.bearing_x).unwrap();
+        writeln!(s, "            bearing_y: {},", c.bearing_y).unwrap();
+
+        // Single line if it fits, otherwise one byte per line (matching rustfmt)
+        let bytes: Vec<_> = c
+            .data
+            .iter()
+            .map(|b| format!("0b{:04b}_{:04b}", b >> 4, b & 0x0f))
+            .collect();
+        let joined = bytes.join(", ");
+        let line = format!("            data: &[{}],", joined);
+
+        if joined.len() <= MAX_ARRAY_WIDTH && line.len() <= MAX_WIDTH {
+            writeln!(s, "{}", line).unwrap();
+        } else {
+            writeln!(s, "            data: &[").unwrap();
+            for b in bytes {
+                writeln!(s, "                {},", b).unwrap();
+            }
+            writeln!(s, "            ],").unwrap();
+        }
+
+        writeln!(s, "        }},").unwrap();
+    }
+
+    writeln!(s, "    ],").unwrap();
+    writeln!(s, "}};").unwrap();
+
+    s
+}
+
+/// Build a rust static name from a font name and size
+fn static_name(font_name: &str, size: u8) -> String {
+    let name: String = font_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    format!("{}_{}PX", name, size)
+}
+
+fn main() -> Result<(), String> {
+    let config = Config::from_args();
+
+    let font_name = PathBuf::from(&config.font)
+        .file_stem()
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_owned();
+
+    let extension = PathBuf::from(&config.font)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase());
+
+    let charset = if config.subset.is_empty() && config.subset_strings.is_empty() {
+        Charset::Range(config.start, config.end)
+    } else {
+        Charset::from_files(&config.subset, &config.subset_strings)?
+    };
+
+    let font_data = fs::read(&config.font).map_err(|e| e.to_string())?;
+
+    let mut rendered = match extension.as_ref().map(|e| e.as_str()) {
+        Some("bdf") => bdf::load(&String::from_utf8_lossy(&font_data), &charset)?,
+        Some("pcf") => pcf::load(&font_data, &charset)?,
+        Some("gz") => return Err("Compressed fonts are not supported, decompress first".into()),
+        _ => ttf::load(font_data, &config, &charset)?,
+    };
+
+    // Sort characters for lookup, bitmap fonts are not necessarily in order
+    rendered.chars.sort_by_key(|c| c.code);
+    rendered.chars.dedup_by_key(|c| c.code);
+
+    // Warn about subset characters the source font can not provide
+    if let Charset::Subset(s) = &charset {
+        for c in s {
+            if rendered.chars.binary_search_by_key(c, |r| r.code).is_err() {
+                eprintln!(
+                    "Warning: character {:?} (U+{:04X}) not found in font {}",
+                    c, *c as u32, config.font
+                );
+            }
+        }
+    }
+
+    let name = match &config.name {
+        Some(n) => n.clone(),
+        None => static_name(&font_name, rendered.size),
+    };
+
+    let chars: Vec<_> = rendered.chars.iter().map(|c| c.as_char()).collect();
+    let font = fonts::Font {
+        size: rendered.size,
+        height: rendered.height,
+        ascent: rendered.ascent,
+        chars: &chars,
+    };
+
+    let output = match config.format {
+        Format::Rust => generate(&name, &font_name, &rendered).into_bytes(),
+        Format::Binary => binary::encode(&font),
+    };
+
+    // Generate file
+    let mut f = File::create(&config.output).map_err(|e| e.to_string())?;
+    f.write_all(&output).map_err(|e| e.to_string())?;
+
+    // Render preview sheet
+    if let Some(p) = &config.preview {
+        let text = config.preview_text.replace("\\n", "\n");
+        preview::render(&font, &text).write(p, config.preview_scale)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bitmap_char() {
+        // Rows are packed most significant bit first and padded to whole bytes
+        let c = bitmap_char('x', 10, 2, 11, -1, 7, |x, y| x == y || x == 9).unwrap();
+        assert_eq!(c.data, vec![0x80, 0x40, 0x40, 0x40]);
+        assert_eq!(
+            (c.width, c.height, c.advance, c.bearing_x, c.bearing_y),
+            (10, 2, 11, -1, 7)
+        );
+
+        let c = bitmap_char('x', 3, 0, 4, 0, 0, |_, _| true).unwrap();
+        assert_eq!(c.data, vec![]);
+
+        let err = bitmap_char('x', 300, 2, 4, 0, 0, |_, _| false).err();
+        assert!(err.unwrap().contains("width (300) out of range"));
+    }
+
+    #[test]
+    fn test_generate() {
+        let chars = vec![
+            bitmap_char('.', 1, 1, 2, 0, 1, |_, _| true).unwrap(),
+            bitmap_char('|', 10, 5, 11, 0, 5, |x, _| x == 9).unwrap(),
+        ];
+        let font = Font {
+            size: 6,
+            height: 7,
+            ascent: 5,
+            chars,
+        };
+
+        // Short data arrays fit on one line, longer ones are split one byte per line
+        let expected = [
+            "//! test 6px bitmap font",
+            "//!",
+            "//! Generated by micro-gui font-converter",
+            "",
+            "use micro_gui::fonts::{Char, Font};",
+            "",
+            "pub static TEST_6PX: Font = Font {",
+            "    size: 6,",
+            "    height: 7,",
+            "    ascent: 5,",
+            "    chars: &[",
+            "        Char {",
+            "            code: '.',",
+            "            width: 1,",
+            "            height: 1,",
+            "            advance: 2,",
+            "            bearing_x: 0,",
+            "            bearing_y: 1,",
+            "            data: &[0b1000_0000],",
+            "        },",
+            "        Char {",
+            "            code: '|',",
+            "            width: 10,",
+            "            height: 5,",
+            "            advance: 11,",
+            "            bearing_x: 0,",
+            "            bearing_y: 5,",
+            "            data: &[",
+        ]
+        .iter()
+        .cloned()
+        .chain((0..5).flat_map(|_| {
+            vec![
+                "                0b0000_0000,",
+                "                0b0100_0000,",
+            ]
+        }))
+        .chain(vec!["            ],", "        },", "    ],", "};"])
+        .collect::<Vec<_>>();
+
+        let s = generate(&static_name("test", 6), "test", &font);
+        assert_eq!(s.lines().collect::<Vec<_>>(), expected);
+    }
+}
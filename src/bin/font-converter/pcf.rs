@@ -0,0 +1,503 @@
+//! PCF (Portable Compiled Format) font import
+//!
+//! See https://fontforge.org/docs/techref/pcf-format.html for the format specification.
+//!
+//! Copyright 2019 Ryan Kurte
+
+use super::subset::Charset;
+use super::{bitmap_char, metric, Font};
+
+const PCF_MAGIC: &[u8] = b"\x01fcp";
+
+const PCF_PROPERTIES: u32 = 1 << 0;
+const PCF_ACCELERATORS: u32 = 1 << 1;
+const PCF_METRICS: u32 = 1 << 2;
+const PCF_BITMAPS: u32 = 1 << 3;
+const PCF_BDF_ENCODINGS: u32 = 1 << 5;
+const PCF_BDF_ACCELERATORS: u32 = 1 << 8;
+
+const PCF_FORMAT_MASK: u32 = 0xffff_ff00;
+const PCF_COMPRESSED_METRICS: u32 = 0x0000_0100;
+
+const PCF_GLYPH_PAD_MASK: u32 = 3;
+const PCF_BYTE_MASK: u32 = 1 << 2;
+const PCF_BIT_MASK: u32 = 1 << 3;
+const PCF_SCAN_UNIT_MASK: u32 = 3 << 4;
+
+/// Glyph metrics from the PCF metrics table
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Metrics {
+    left_bearing: i32,
+    right_bearing: i32,
+    width: i32,
+    ascent: i32,
+    descent: i32,
+}
+
+/// Reader over a single PCF table, respecting the table byte order
+struct Table<'a> {
+    data: &'a [u8],
+    format: u32,
+    index: usize,
+}
+
+impl<'a> Table<'a> {
+    /// Locate a table in the font by type, reading the table format
+    fn find(font: &'a [u8], kind: u32) -> Result<Option<Table<'a>>, String> {
+        if font.get(..4) != Some(PCF_MAGIC) {
+            return Err("invalid PCF header".into());
+        }
+
+        // The table of contents is always little endian
+        let mut toc = Table {
+            data: font,
+            format: 0,
+            index: 4,
+        };
+
+        let count = toc.u32()?;
+        for _ in 0..count {
+            let (t, _format, size, offset) = (toc.u32()?, toc.u32()?, toc.u32()?, toc.u32()?);
+            if t != kind {
+                continue;
+            }
+
+            let (start, end) = (offset as usize, offset as usize + size as usize);
+            let data = font
+                .get(start..end)
+                .ok_or_else(|| format!("PCF table {:#x} out of bounds", kind))?;
+
+            let mut table = Table {
+                data,
+                format: 0,
+                index: 0,
+            };
+            table.format = table.u32()?;
+
+            return Ok(Some(table));
+        }
+
+        Ok(None)
+    }
+
+    /// Fetch a required table, failing if it does not exist
+    fn require(font: &'a [u8], kind: u32, name: &str) -> Result<Table<'a>, String> {
+        Self::find(font, kind)?.ok_or_else(|| format!("PCF font missing {} table", name))
+    }
+
+    fn big_endian(&self) -> bool {
+        self.format & PCF_BYTE_MASK != 0
+    }
+
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], String> {
+        let b = self
+            .data
+            .get(self.index..self.index + n)
+            .ok_or("unexpected end of PCF table")?;
+        self.index += n;
+        Ok(b)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        let b = [self.u8()?, self.u8()?];
+        match self.big_endian() {
+            true => Ok(u16::from_be_bytes(b)),
+            false => Ok(u16::from_le_bytes(b)),
+        }
+    }
+
+    fn i16(&mut self) -> Result<i16, String> {
+        self.u16().map(|v| v as i16)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let b = [self.u8()?, self.u8()?, self.u8()?, self.u8()?];
+        match self.big_endian() {
+            true => Ok(u32::from_be_bytes(b)),
+            false => Ok(u32::from_le_bytes(b)),
+        }
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        self.u32().map(|v| v as i32)
+    }
+}
+
+/// Read glyph metrics from the metrics table
+fn read_metrics(t: &mut Table) -> Result<Vec<Metrics>, String> {
+    let compressed = t.format & PCF_FORMAT_MASK == PCF_COMPRESSED_METRICS;
+
+    let count = match compressed {
+        true => t.i16()? as usize,
+        false => t.i32()? as usize,
+    };
+
+    (0..count)
+        .map(|_| {
+            if compressed {
+                let mut v = [0i32; 5];
+                for m in v.iter_mut() {
+                    *m = t.u8()? as i32 - 0x80;
+                }
+                Ok(Metrics {
+                    left_bearing: v[0],
+                    right_bearing: v[1],
+                    width: v[2],
+                    ascent: v[3],
+                    descent: v[4],
+                })
+            } else {
+                let m = Metrics {
+                    left_bearing: t.i16()? as i32,
+                    right_bearing: t.i16()? as i32,
+                    width: t.i16()? as i32,
+                    ascent: t.i16()? as i32,
+                    descent: t.i16()? as i32,
+                };
+                // Skip attributes
+                t.u16()?;
+                Ok(m)
+            }
+        })
+        .collect()
+}
+
+/// Read the bitmap table, returning per-glyph offsets and bitmap data
+/// normalised to MSB first bit and byte order
+fn read_bitmaps(t: &mut Table) -> Result<(Vec<usize>, Vec<u8>), String> {
+    let count = t.i32()? as usize;
+    let offsets = (0..count)
+        .map(|_| t.u32().map(|o| o as usize))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut sizes = [0usize; 4];
+    for s in sizes.iter_mut() {
+        *s = t.u32()? as usize;
+    }
+
+    let size = sizes[(t.format & PCF_GLYPH_PAD_MASK) as usize];
+    let mut data = t.bytes(size)?.to_vec();
+
+    // Swap bytes within scan units where byte and bit order differ
+    let byte_msb = t.format & PCF_BYTE_MASK != 0;
+    let bit_msb = t.format & PCF_BIT_MASK != 0;
+    let scan_unit = 1 << ((t.format & PCF_SCAN_UNIT_MASK) >> 4);
+    if byte_msb != bit_msb && scan_unit > 1 {
+        for unit in data.chunks_mut(scan_unit) {
+            unit.reverse();
+        }
+    }
+
+    // Normalise bit order to MSB (leftmost pixel) first
+    if !bit_msb {
+        for b in data.iter_mut() {
+            *b = b.reverse_bits();
+        }
+    }
+
+    Ok((offsets, data))
+}
+
+/// Read the encoding table, returning (code, glyph index) pairs
+fn read_encodings(t: &mut Table) -> Result<Vec<(u32, usize)>, String> {
+    let (min_byte2, max_byte2) = (t.i16()? as u32, t.i16()? as u32);
+    let (min_byte1, max_byte1) = (t.i16()? as u32, t.i16()? as u32);
+    let _default_char = t.i16()?;
+
+    let mut encodings = Vec::new();
+    for byte1 in min_byte1..=max_byte1 {
+        for byte2 in min_byte2..=max_byte2 {
+            let index = t.u16()?;
+            if index != 0xffff {
+                encodings.push(((byte1 << 8) | byte2, index as usize));
+            }
+        }
+    }
+
+    Ok(encodings)
+}
+
+/// Read the PIXEL_SIZE property, if available
+fn read_pixel_size(t: &mut Table) -> Result<Option<i32>, String> {
+    let count = t.i32()? as usize;
+
+    let mut props = Vec::with_capacity(count);
+    for _ in 0..count {
+        let (name, is_string, value) = (t.u32()? as usize, t.u8()?, t.i32()?);
+        props.push((name, is_string, value));
+    }
+
+    // Properties are padded to a 4 byte boundary
+    if count % 4 != 0 {
+        t.bytes(4 - count % 4)?;
+    }
+
+    let strings_len = t.u32()? as usize;
+    let strings = t.bytes(strings_len)?;
+
+    let pixel_size = props.iter().find(|(name, is_string, _)| {
+        *is_string == 0
+            && strings.get(*name..).map(|s| s.starts_with(b"PIXEL_SIZE\0")) == Some(true)
+    });
+
+    Ok(pixel_size.map(|p| p.2))
+}
+
+/// Load a PCF font, retaining characters in the provided set
+pub fn load(data: &[u8], charset: &Charset) -> Result<Font, String> {
+    let metrics = read_metrics(&mut Table::require(data, PCF_METRICS, "metrics")?)?;
+    let encodings = read_encodings(&mut Table::require(data, PCF_BDF_ENCODINGS, "encodings")?)?;
+
+    let mut bitmap_table = Table::require(data, PCF_BITMAPS, "bitmaps")?;
+    let pad = 1 << (bitmap_table.format & PCF_GLYPH_PAD_MASK);
+    let (offsets, bitmaps) = read_bitmaps(&mut bitmap_table)?;
+
+    // Font ascent and descent are stored in the accelerator table
+    let mut accel = match Table::find(data, PCF_BDF_ACCELERATORS)? {
+        Some(t) => t,
+        None => Table::require(data, PCF_ACCELERATORS, "accelerators")?,
+    };
+    accel.bytes(8)?;
+    let (ascent, descent) = (accel.i32()?, accel.i32()?);
+
+    let pixel_size = match Table::find(data, PCF_PROPERTIES)? {
+        Some(mut t) => read_pixel_size(&mut t)?,
+        None => None,
+    };
+
+    let mut chars = Vec::new();
+
+    for (code, index) in encodings {
+        let code = match std::char::from_u32(code) {
+            Some(c) if charset.contains(c) => c,
+            _ => continue,
+        };
+
+        let m = metrics
+            .get(index)
+            .ok_or_else(|| format!("PCF glyph index {} out of range", index))?;
+        let offset = *offsets
+            .get(index)
+            .ok_or_else(|| format!("PCF glyph index {} out of range", index))?;
+
+        let width = m.right_bearing - m.left_bearing;
+        let height = m.ascent + m.descent;
+
+        // Rows are padded to the glyph pad size
+        let stride = (width.max(0) as usize).div_ceil(8).div_ceil(pad) * pad;
+        if offset + stride * height.max(0) as usize > bitmaps.len() {
+            return Err(format!("PCF bitmap for character {:?} out of range", code));
+        }
+
+        let c = bitmap_char(
+            code,
+            width,
+            height,
+            m.width,
+            m.left_bearing,
+            m.ascent,
+            |x, y| bitmaps[offset + y * stride + x / 8] & (0x80 >> (x % 8)) != 0,
+        )?;
+
+        chars.push(c);
+    }
+
+    let height = ascent + descent;
+
+    Ok(Font {
+        size: metric("size", ' ', pixel_size.unwrap_or(height))?,
+        height: metric("line height", ' ', height)?,
+        ascent: metric("ascent", ' ', ascent)?,
+        chars,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Table writer used to assemble test fonts
+    struct Writer {
+        format: u32,
+        data: Vec<u8>,
+    }
+
+    impl Writer {
+        fn new(format: u32) -> Self {
+            Self {
+                format,
+                data: format.to_le_bytes().to_vec(),
+            }
+        }
+
+        fn u8(&mut self, v: u8) {
+            self.data.push(v);
+        }
+
+        fn u16(&mut self, v: u16) {
+            match self.format & PCF_BYTE_MASK != 0 {
+                true => self.data.extend_from_slice(&v.to_be_bytes()),
+                false => self.data.extend_from_slice(&v.to_le_bytes()),
+            }
+        }
+
+        fn u32(&mut self, v: u32) {
+            match self.format & PCF_BYTE_MASK != 0 {
+                true => self.data.extend_from_slice(&v.to_be_bytes()),
+                false => self.data.extend_from_slice(&v.to_le_bytes()),
+            }
+        }
+    }
+
+    /// Assemble a PCF file from a set of tables
+    fn assemble(tables: Vec<(u32, Writer)>) -> Vec<u8> {
+        let mut font = PCF_MAGIC.to_vec();
+        font.extend_from_slice(&(tables.len() as u32).to_le_bytes());
+
+        let mut offset = 8 + tables.len() * 16;
+        for (kind, t) in &tables {
+            for v in &[*kind, t.format, t.data.len() as u32, offset as u32] {
+                font.extend_from_slice(&v.to_le_bytes());
+            }
+            offset += t.data.len();
+        }
+        for (_, t) in tables {
+            font.extend_from_slice(&t.data);
+        }
+
+        font
+    }
+
+    /// Build a two character font ('A' and 'B') using the provided bitmap format
+    fn font(bitmap_format: u32, compressed: bool) -> Vec<u8> {
+        let mut props = Writer::new(PCF_BYTE_MASK);
+        props.u32(1);
+        props.u32(0);
+        props.u8(0);
+        props.u32(6);
+        props.data.extend_from_slice(&[0, 0, 0]);
+        props.u32(11);
+        props.data.extend_from_slice(b"PIXEL_SIZE\0");
+
+        let mut accel = Writer::new(PCF_BYTE_MASK);
+        accel.data.extend_from_slice(&[0; 8]);
+        accel.u32(5);
+        accel.u32(1);
+
+        // (left, right, width, ascent, descent) for 'A' then 'B'
+        let glyphs = [[0, 3, 4, 4, 0], [0, 3, 4, 5, 0]];
+        let metrics = match compressed {
+            true => {
+                let mut m = Writer::new(PCF_COMPRESSED_METRICS);
+                m.u16(2);
+                for g in glyphs.iter() {
+                    for v in g {
+                        m.u8((v + 0x80) as u8);
+                    }
+                }
+                m
+            }
+            false => {
+                let mut m = Writer::new(PCF_BYTE_MASK);
+                m.u32(2);
+                for g in glyphs.iter() {
+                    for v in g {
+                        m.u16(*v as u16);
+                    }
+                    m.u16(0);
+                }
+                m
+            }
+        };
+
+        // Glyph rows (MSB first), padded to 4 bytes per row
+        let rows: [&[u8]; 2] = [&[0x40, 0xA0, 0xE0, 0xA0], &[0xC0, 0xA0, 0xC0, 0xA0, 0xC0]];
+        let mut bitmap = vec![];
+        let mut offsets = vec![];
+        for r in rows.iter() {
+            offsets.push(bitmap.len() as u32);
+            for b in r.iter() {
+                let b = match bitmap_format & PCF_BIT_MASK != 0 {
+                    true => *b,
+                    false => b.reverse_bits(),
+                };
+                let mut unit = [b, 0, 0, 0];
+                if (bitmap_format & PCF_BYTE_MASK != 0) != (bitmap_format & PCF_BIT_MASK != 0) {
+                    unit.reverse();
+                }
+                bitmap.extend_from_slice(&unit);
+            }
+        }
+
+        let mut bitmaps = Writer::new(bitmap_format);
+        bitmaps.u32(2);
+        for o in offsets {
+            bitmaps.u32(o);
+        }
+        for _ in 0..4 {
+            bitmaps.u32(bitmap.len() as u32);
+        }
+        bitmaps.data.extend_from_slice(&bitmap);
+
+        let mut encodings = Writer::new(PCF_BYTE_MASK);
+        for v in &[65, 66, 0, 0, 0] {
+            encodings.u16(*v);
+        }
+        encodings.u16(0);
+        encodings.u16(1);
+
+        assemble(vec![
+            (PCF_PROPERTIES, props),
+            (PCF_BDF_ACCELERATORS, accel),
+            (PCF_METRICS, metrics),
+            (PCF_BITMAPS, bitmaps),
+            (PCF_BDF_ENCODINGS, encodings),
+        ])
+    }
+
+    const ASCII: Charset = Charset::Range(32, 127);
+
+    #[test]
+    fn test_load_pcf() {
+        // Pad to 4 bytes, 4 byte scan units, for each byte and bit order
+        let formats = [
+            0x22,
+            0x22 | PCF_BYTE_MASK,
+            0x22 | PCF_BIT_MASK,
+            0x22 | PCF_BYTE_MASK | PCF_BIT_MASK,
+        ];
+
+        for format in formats.iter() {
+            for compressed in &[false, true] {
+                let font = load(&font(*format, *compressed), &ASCII).unwrap();
+
+                assert_eq!(font.size, 6);
+                assert_eq!(font.height, 6);
+                assert_eq!(font.ascent, 5);
+                assert_eq!(font.chars.len(), 2);
+
+                let a = &font.chars[0];
+                assert_eq!(a.code, 'A');
+                assert_eq!((a.width, a.height, a.advance), (3, 4, 4));
+                assert_eq!((a.bearing_x, a.bearing_y), (0, 4));
+                assert_eq!(a.data, vec![0x40, 0xA0, 0xE0, 0xA0]);
+
+                let b = &font.chars[1];
+                assert_eq!(b.code, 'B');
+                assert_eq!(b.data, vec![0xC0, 0xA0, 0xC0, 0xA0, 0xC0]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_load_pcf_invalid() {
+        assert!(load(b"not a font", &ASCII).is_err());
+
+        let font = font(0x22, false);
+        assert!(load(&font[..font.len() - 8], &ASCII).is_err());
+    }
+}
@@ -0,0 +1,282 @@
+//! Font preview rendering, using the micro-gui text renderer so previews
+//! match on-device output
+//!
+//! Copyright 2019 Ryan Kurte
+
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::BufWriter;
+use std::path::Path;
+
+use micro_gui::core::buffer::{Buff, Buffer};
+use micro_gui::fonts::{Font, Glyphs};
+use micro_gui::graphics::stroke::Stroke;
+use micro_gui::graphics::Graphics;
+use micro_gui::types::pixel::PixelBW;
+use micro_gui::types::Point;
+
+/// Margin around the preview sheet in pixels
+const MARGIN: usize = 4;
+/// Spacing between characters in the specimen grid
+const SPACING: usize = 2;
+/// Number of characters per row in the specimen grid
+const COLUMNS: usize = 16;
+
+/// Rendered preview sheet, stored in `Buffer<PixelBW>` layout (black pixels set)
+pub struct Preview {
+    pub width: usize,
+    pub height: usize,
+    pub data: Vec<u8>,
+}
+
+/// Render a specimen grid of all characters in a font, followed by a sample string
+pub fn render(font: &Font, sample: &str) -> Preview {
+    let ascent = font.ascent as isize;
+
+    // Compute grid cell size to fit all characters, including those exceeding the line bounds
+    let left = font.chars.iter().map(|c| c.bearing_x as isize).min();
+    let right = font
+        .chars
+        .iter()
+        .map(|c| (c.advance as isize).max(c.bearing_x as isize + c.width as isize))
+        .max();
+    let top = font
+        .chars
+        .iter()
+        .map(|c| ascent - c.bearing_y as isize)
+        .min();
+    let bottom = font
+        .chars
+        .iter()
+        .map(|c| ascent - c.bearing_y as isize + c.height as isize)
+        .max();
+
+    let x_off = (-left.unwrap_or(0)).max(0) as usize;
+    let y_off = (-top.unwrap_or(0)).max(0) as usize;
+    let cell_w = x_off + right.unwrap_or(0).max(0) as usize + SPACING;
+    let cell_h = y_off + bottom.unwrap_or(0).max(font.height as isize) as usize + SPACING;
+
+    let rows = font.chars.len().div_ceil(COLUMNS);
+    let grid_w = cell_w * COLUMNS.min(font.chars.len());
+    let grid_h = cell_h * rows;
+
+    let sample_lines = sample.split('\n').count();
+    let sample_w = font.text_width(sample);
+    let sample_h = sample_lines * font.height as usize;
+
+    // Buffer width must be a whole number of bytes
+    let width = (grid_w.max(sample_w) + MARGIN * 2).div_ceil(8) * 8;
+    let height = grid_h + sample_h + MARGIN * 4;
+
+    let mut data = vec![0u8; width / 8 * height];
+    {
+        let mut buffer = Buffer::<PixelBW>::new(width, height, 0, 0, &mut data);
+        let graphics = Graphics::new(0, 0, width, height);
+
+        for (i, c) in font.chars.iter().enumerate() {
+            let pos = Point::from_unsigned(
+                MARGIN + (i % COLUMNS) * cell_w + x_off,
+                MARGIN + (i / COLUMNS) * cell_h + y_off,
+            );
+            graphics.draw_char(&mut buffer, pos, font, c.code, &true);
+        }
+
+        let rule_y = MARGIN * 2 + grid_h;
+        graphics.draw_line(
+            &mut buffer,
+            Point::from_unsigned(MARGIN, rule_y),
+            Point::from_unsigned(width - MARGIN, rule_y),
+            &Stroke::default(),
+            &true,
+        );
+
+        graphics.draw_text(
+            &mut buffer,
+            Point::from_unsigned(MARGIN, rule_y + MARGIN),
+            font,
+            sample,
+            &true,
+        );
+    }
+
+    Preview {
+        width,
+        height,
+        data,
+    }
+}
+
+impl Preview {
+    /// Scale the preview by an integer factor (nearest neighbour)
+    fn scale(self, scale: usize) -> Preview {
+        if scale <= 1 {
+            return self;
+        }
+
+        let (width, height) = (self.width * scale, self.height * scale);
+        let mut data = self.data;
+        let source = Buffer::<PixelBW>::new(self.width, self.height, 0, 0, &mut data);
+
+        let mut scaled = vec![0u8; width / 8 * height];
+        {
+            let mut buffer = Buffer::<PixelBW>::new(width, height, 0, 0, &mut scaled);
+            for y in 0..height {
+                for x in 0..width {
+                    buffer.set(x, y, &source.get(x / scale, y / scale));
+                }
+            }
+        }
+
+        Preview {
+            width,
+            height,
+            data: scaled,
+        }
+    }
+
+    /// Write the preview as a binary PBM image
+    fn write_pbm(&self, path: &Path) -> Result<(), String> {
+        let mut f = BufWriter::new(File::create(path).map_err(|e| e.to_string())?);
+
+        // PBM rows are packed MSB first with black pixels set, matching the buffer layout
+        write!(f, "P4\n{} {}\n", self.width, self.height).map_err(|e| e.to_string())?;
+        f.write_all(&self.data).map_err(|e| e.to_string())
+    }
+
+    /// Write the preview as a 1-bit greyscale PNG image
+    fn write_png(&self, path: &Path) -> Result<(), String> {
+        let f = BufWriter::new(File::create(path).map_err(|e| e.to_string())?);
+
+        let mut encoder = png::Encoder::new(f, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::One);
+
+        // PNG greyscale is zero for black, so invert the buffer
+        let data: Vec<u8> = self.data.iter().map(|b| !b).collect();
+
+        let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+        writer.write_image_data(&data).map_err(|e| e.to_string())
+    }
+
+    /// Write the preview to a file, with the format (PNG or PBM) selected by extension
+    pub fn write(self, path: &str, scale: usize) -> Result<(), String> {
+        let path = Path::new(path);
+        let preview = self.scale(scale);
+
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("png") => preview.write_png(path),
+            Some("pbm") => preview.write_pbm(path),
+            _ => Err(format!(
+                "Unsupported preview format: {} (expected .png or .pbm)",
+                path.display()
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use micro_gui::fonts::Char;
+
+    /// Two glyph font with a 2x2 block 'a' and a 1x3 bar 'b'
+    fn font() -> Font<'static> {
+        static CHARS: [Char; 2] = [
+            Char {
+                code: 'a',
+                width: 2,
+                height: 2,
+                advance: 3,
+                bearing_x: 0,
+                bearing_y: 2,
+                data: &[0b1100_0000, 0b1100_0000],
+            },
+            Char {
+                code: 'b',
+                width: 1,
+                height: 3,
+                advance: 3,
+                bearing_x: 0,
+                bearing_y: 3,
+                data: &[0b1000_0000, 0b1000_0000, 0b1000_0000],
+            },
+        ];
+        Font {
+            size: 4,
+            height: 4,
+            ascent: 3,
+            chars: &CHARS,
+        }
+    }
+
+    #[test]
+    fn test_write_pbm() {
+        let path = std::env::temp_dir().join("micro-gui-preview-test.pbm");
+        render(&font(), "ab")
+            .write(path.to_str().unwrap(), 1)
+            .unwrap();
+        let data = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let header = b"P4\n24 26\n";
+        assert_eq!(&data[..header.len()], header);
+
+        // Glyph grid, then a rule and the sample text, packed MSB first with black pixels set
+        let expected = [
+            "........................",
+            "........................",
+            "........................",
+            "........................",
+            ".........#..............",
+            "....##...#..............",
+            "....##...#..............",
+            "........................",
+            "........................",
+            "........................",
+            "........................",
+            "........................",
+            "........................",
+            "........................",
+            "....#################...",
+            "........................",
+            "........................",
+            "........................",
+            ".......#................",
+            "....##.#................",
+            "....##.#................",
+            "........................",
+            "........................",
+            "........................",
+            "........................",
+            "........................",
+        ];
+        let rows: Vec<String> = data[header.len()..]
+            .chunks(3)
+            .map(|r| r.iter().map(|b| format!("{:08b}", b)).collect())
+            .collect();
+        assert_eq!(
+            rows,
+            expected
+                .iter()
+                .map(|r| r.replace('.', "0").replace('#', "1"))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_write_png() {
+        let path = std::env::temp_dir().join("micro-gui-preview-test.png");
+        render(&font(), "ab")
+            .write(path.to_str().unwrap(), 2)
+            .unwrap();
+        let mut decoder = png::Decoder::new(File::open(&path).unwrap());
+        decoder.set_transformations(png::Transformations::IDENTITY);
+        let (info, _) = decoder.read_info().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!((info.width, info.height), (48, 52));
+        assert_eq!(info.color_type, png::ColorType::Grayscale);
+        assert_eq!(info.bit_depth, png::BitDepth::One);
+    }
+}
@@ -0,0 +1,151 @@
+//! Character set selection, either from a code range or a subset of characters
+//! used in application text
+//!
+//! Copyright 2019 Ryan Kurte
+
+use std::collections::BTreeSet;
+use std::fs;
+
+/// Set of characters to be converted
+#[derive(Clone, Debug, PartialEq)]
+pub enum Charset {
+    /// Range of character codes (end exclusive)
+    Range(u32, u32),
+    /// Explicit set of characters
+    Subset(BTreeSet<char>),
+}
+
+impl Charset {
+    /// Check whether the set contains a character
+    pub fn contains(&self, c: char) -> bool {
+        match self {
+            Charset::Range(start, end) => *start <= c as u32 && (c as u32) < *end,
+            Charset::Subset(s) => s.contains(&c),
+        }
+    }
+
+    /// Fetch the characters in the set, in code point order
+    pub fn chars(&self) -> Vec<char> {
+        match self {
+            Charset::Range(start, end) => (*start..*end).filter_map(std::char::from_u32).collect(),
+            Charset::Subset(s) => s.iter().cloned().collect(),
+        }
+    }
+
+    /// Build a subset from plain text files and string table files
+    ///
+    /// All characters in text files are used, while only the contents of double quoted
+    /// string literals are used from string table files (for example rust or C sources,
+    /// JSON, or gettext catalogues).
+    pub fn from_files(text: &[String], strings: &[String]) -> Result<Self, String> {
+        let mut set = BTreeSet::new();
+
+        for f in text {
+            let t = fs::read_to_string(f).map_err(|e| format!("{}: {}", f, e))?;
+            set.extend(t.chars());
+        }
+
+        for f in strings {
+            let t = fs::read_to_string(f).map_err(|e| format!("{}: {}", f, e))?;
+            for s in string_literals(&t) {
+                set.extend(s.chars());
+            }
+        }
+
+        // Newlines, tabs etc. are never rendered
+        set.retain(|c| !c.is_control());
+
+        Ok(Charset::Subset(set))
+    }
+}
+
+/// Parse a fixed number of hex digits from a character stream
+fn hex<I: Iterator<Item = char>>(chars: &mut I, digits: usize) -> Option<char> {
+    let s: String = chars.take(digits).collect();
+    u32::from_str_radix(&s, 16)
+        .ok()
+        .and_then(std::char::from_u32)
+}
+
+/// Extract the contents of double quoted string literals, processing common escapes
+/// (`\n`, `\t`, `\xNN`, `\uNNNN`, and `\u{N...}`)
+pub fn string_literals(text: &str) -> Vec<String> {
+    let mut strings = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '"' {
+            continue;
+        }
+
+        let mut s = String::new();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => break,
+                '\\' => match chars.next() {
+                    Some('n') => s.push('\n'),
+                    Some('t') => s.push('\t'),
+                    Some('r') => s.push('\r'),
+                    Some('0') => s.push('\0'),
+                    Some('x') => s.extend(hex(&mut chars, 2)),
+                    Some('u') if chars.peek() == Some(&'{') => {
+                        chars.next();
+                        let digits: String = chars.by_ref().take_while(|c| *c != '}').collect();
+                        s.extend(
+                            u32::from_str_radix(&digits, 16)
+                                .ok()
+                                .and_then(std::char::from_u32),
+                        );
+                    }
+                    Some('u') => s.extend(hex(&mut chars, 4)),
+                    Some(c) => s.push(c),
+                    None => break,
+                },
+                _ => s.push(c),
+            }
+        }
+
+        strings.push(s);
+    }
+
+    strings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_string_literals() {
+        let text = r#"
+            pub const TITLE: &str = "Temp: 25\u{b0}C";
+            { "menu": "Menü \"Start\"", "count": 3 }
+            msgid "Back\\Next\n"
+        "#;
+
+        assert_eq!(
+            string_literals(text),
+            vec![
+                "Temp: 25\u{b0}C",
+                "menu",
+                "Men\u{fc} \"Start\"",
+                "count",
+                "Back\\Next\n"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_charset() {
+        let range = Charset::Range(0x41, 0x44);
+        assert!(range.contains('A'));
+        assert!(!range.contains('D'));
+        assert_eq!(range.chars(), vec!['A', 'B', 'C']);
+
+        let subset = Charset::Subset("ZAz".chars().collect());
+        assert!(subset.contains('z'));
+        assert!(!subset.contains('B'));
+        assert_eq!(subset.chars(), vec!['A', 'Z', 'z']);
+    }
+}
@@ -0,0 +1,183 @@
+//! TTF/OTF font rasterisation
+//!
+//! Copyright 2019 Ryan Kurte
+
+use rusttype::{point, Scale};
+
+use super::subset::Charset;
+use super::{metric, Char, Config, Font};
+
+/// Pack a row-major glyph bitmap, trimming any empty rows and columns
+///
+/// Returns the trimmed width, height, x and y bearings, and the packed data
+fn pack(
+    width: usize,
+    pixels: &[bool],
+    bearing_x: i32,
+    bearing_y: i32,
+) -> (i32, i32, i32, i32, Vec<u8>) {
+    let set = pixels
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| **p)
+        .map(|(i, _)| (i % width, i / width));
+
+    let (mut x0, mut y0, mut x1, mut y1) = (usize::MAX, usize::MAX, 0, 0);
+    for (x, y) in set {
+        x0 = x0.min(x);
+        y0 = y0.min(y);
+        x1 = x1.max(x + 1);
+        y1 = y1.max(y + 1);
+    }
+
+    // Nothing visible, emit an empty glyph
+    if x0 >= x1 {
+        return (0, 0, 0, 0, vec![]);
+    }
+
+    let (w, h) = (x1 - x0, y1 - y0);
+    let stride = w.div_ceil(8);
+    let mut data = vec![0u8; stride * h];
+
+    for y in 0..h {
+        for x in 0..w {
+            if pixels[(y0 + y) * width + x0 + x] {
+                data[y * stride + x / 8] |= 0x80 >> (x % 8);
+            }
+        }
+    }
+
+    (
+        w as i32,
+        h as i32,
+        bearing_x + x0 as i32,
+        bearing_y - y0 as i32,
+        data,
+    )
+}
+
+/// Rasterise the provided character set from font data, skipping characters
+/// without a glyph in the font
+pub fn load(data: Vec<u8>, config: &Config, charset: &Charset) -> Result<Font, String> {
+    let font = rusttype::Font::try_from_vec(data)
+        .ok_or_else(|| format!("Failed to parse font: {}", config.font))?;
+
+    let scale = Scale::uniform(config.size as f32);
+    let v_metrics = font.v_metrics(scale);
+
+    let ascent = v_metrics.ascent.ceil() as i32;
+    let height = (v_metrics.ascent - v_metrics.descent + v_metrics.line_gap).ceil() as i32;
+
+    let mut chars = Vec::new();
+
+    for code in charset.chars() {
+        let glyph = font.glyph(code);
+
+        // Glyph zero is the missing character glyph
+        if glyph.id().0 == 0 {
+            continue;
+        }
+
+        let glyph = glyph.scaled(scale);
+        let advance = glyph.h_metrics().advance_width.round() as i32;
+
+        // Position glyph with the pen on the baseline at the origin
+        let glyph = glyph.positioned(point(0.0, 0.0));
+
+        let (width, height, bearing_x, bearing_y, data) = match glyph.pixel_bounding_box() {
+            Some(bb) => {
+                let width = bb.width() as usize;
+                let mut pixels = vec![false; width * bb.height() as usize];
+
+                glyph.draw(|x, y, v| {
+                    pixels[y as usize * width + x as usize] = v >= config.threshold;
+                });
+
+                pack(width, &pixels, bb.min.x, -bb.min.y)
+            }
+            None => (0, 0, 0, 0, vec![]),
+        };
+
+        chars.push(Char {
+            code,
+            width: metric("width", code, width)?,
+            height: metric("height", code, height)?,
+            advance: metric("advance", code, advance)?,
+            bearing_x: metric("x bearing", code, bearing_x)?,
+            bearing_y: metric("y bearing", code, bearing_y)?,
+            data,
+        });
+    }
+
+    Ok(Font {
+        size: metric("size", ' ', config.size as i32)?,
+        height: metric("line height", ' ', height)?,
+        ascent: metric("ascent", ' ', ascent)?,
+        chars,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use structopt::StructOpt;
+
+    #[test]
+    fn test_pack() {
+        // Empty rows and columns are trimmed, moving the bearings to the set pixels
+        let mut pixels = vec![false; 30];
+        for &(x, y) in &[(1, 1), (9, 1), (5, 2)] {
+            pixels[y * 10 + x] = true;
+        }
+        assert_eq!(
+            pack(10, &pixels, -2, 7),
+            (9, 2, -1, 6, vec![0x80, 0x80, 0x08, 0x00])
+        );
+
+        assert_eq!(pack(10, &[false; 30], -2, 7), (0, 0, 0, 0, vec![]));
+    }
+
+    #[test]
+    fn test_load() {
+        let config = Config::from_iter(&["font-converter", "--size", "12"]);
+        let data = include_bytes!("../../../fonts/SourceCodePro-Regular.ttf").to_vec();
+        let font = load(data, &config, &Charset::Range(' ' as u32, 'C' as u32)).unwrap();
+
+        assert_eq!((font.size, font.height, font.ascent), (12, 12, 10));
+        assert_eq!(font.chars.len(), 35);
+
+        for c in &font.chars {
+            // Monospaced advances, with packed rows padded to whole bytes
+            let stride = (c.width as usize).div_ceil(8);
+            assert_eq!(c.advance, 6, "{:?}", c.code);
+            assert_eq!(c.data.len(), stride * c.height as usize, "{:?}", c.code);
+            assert!(c.bearing_y <= font.ascent as i8, "{:?}", c.code);
+            for row in c.data.chunks(stride.max(1)) {
+                let padding = stride * 8 - c.width as usize;
+                assert_eq!(
+                    row[stride - 1] & ((1 << padding) - 1) as u8,
+                    0,
+                    "{:?}",
+                    c.code
+                );
+            }
+        }
+
+        // Glyphs are positioned relative to the pen on the baseline
+        let get = |code| font.chars.iter().find(|c| c.code == code).unwrap();
+        assert_eq!(get(' ').data, vec![]);
+        let dot = get('.');
+        assert_eq!(
+            (dot.width, dot.height, dot.bearing_x, dot.bearing_y),
+            (2, 1, 2, 1)
+        );
+        assert_eq!(dot.data, vec![0b1100_0000]);
+        let dash = get('-');
+        assert_eq!(
+            (dash.width, dash.height, dash.bearing_x, dash.bearing_y),
+            (4, 1, 1, 4)
+        );
+        assert_eq!(dash.data, vec![0b1111_0000]);
+    }
+}
@@ -0,0 +1,271 @@
+//! BMP (Windows bitmap) image import
+//!
+//! Supports uncompressed 1, 4, 8, 16, 24 and 32 bit images, and 16 and 32 bit images with
+//! bit field masks (including alpha masks from V3 and later headers).
+//!
+//! Copyright 2019 Ryan Kurte
+
+use super::image::Image;
+
+const FILE_HEADER_LEN: usize = 14;
+const INFO_HEADER_LEN: usize = 40;
+
+const BI_RGB: u32 = 0;
+const BI_BITFIELDS: u32 = 3;
+
+/// Channel bit field, used to extract and scale a colour channel to 8 bits
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Field {
+    shift: u32,
+    bits: u32,
+}
+
+impl Field {
+    fn new(mask: u32) -> Field {
+        match mask {
+            0 => Field { shift: 0, bits: 0 },
+            m => Field {
+                shift: m.trailing_zeros(),
+                bits: (m >> m.trailing_zeros()).trailing_ones(),
+            },
+        }
+    }
+
+    /// Extract a channel value, or the default value if the channel is not present
+    fn get(&self, v: u32, default: u8) -> u8 {
+        if self.bits == 0 {
+            return default;
+        }
+
+        let max = (1u64 << self.bits) - 1;
+        let c = (v >> self.shift) as u64 & max;
+        ((c * 255 + max / 2) / max) as u8
+    }
+}
+
+fn u16_at(data: &[u8], offset: usize) -> Result<u16, String> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .ok_or_else(|| "BMP header truncated".to_string())
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Result<u32, String> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| "BMP header truncated".to_string())
+}
+
+/// Load a BMP image
+pub fn load(data: &[u8]) -> Result<Image, String> {
+    if data.get(..2) != Some(b"BM") {
+        return Err("invalid BMP header".into());
+    }
+
+    let offset = u32_at(data, 10)? as usize;
+    let header_len = u32_at(data, FILE_HEADER_LEN)? as usize;
+    if header_len < INFO_HEADER_LEN {
+        return Err(format!("unsupported BMP header size: {}", header_len));
+    }
+
+    let width = u32_at(data, 18)? as i32;
+    let height = u32_at(data, 22)? as i32;
+    let bpp = u16_at(data, 28)?;
+    let compression = u32_at(data, 30)?;
+    let colours = u32_at(data, 46)? as usize;
+
+    if width <= 0 || height == 0 {
+        return Err(format!("invalid BMP size: {}x{}", width, height));
+    }
+
+    // Rows are stored bottom up unless the height is negative
+    let (width, bottom_up) = (width as usize, height > 0);
+    let height = height.unsigned_abs() as usize;
+
+    // Bit field masks follow the info header, or are part of later headers
+    let fields = match (compression, bpp) {
+        (BI_RGB, 1) | (BI_RGB, 4) | (BI_RGB, 8) | (BI_RGB, 24) => None,
+        (BI_RGB, 16) => Some([0x7c00, 0x03e0, 0x001f, 0]),
+        (BI_RGB, 32) => Some([0x00ff_0000, 0x0000_ff00, 0x0000_00ff, 0]),
+        (BI_BITFIELDS, 16) | (BI_BITFIELDS, 32) => {
+            let m = FILE_HEADER_LEN + INFO_HEADER_LEN;
+            let alpha = match header_len {
+                l if l > INFO_HEADER_LEN + 12 => u32_at(data, m + 12)?,
+                _ => 0,
+            };
+            Some([
+                u32_at(data, m)?,
+                u32_at(data, m + 4)?,
+                u32_at(data, m + 8)?,
+                alpha,
+            ])
+        }
+        _ => {
+            return Err(format!(
+                "unsupported BMP format: {} bits per pixel, compression {}",
+                bpp, compression
+            ))
+        }
+    };
+    let fields = fields.map(|f| {
+        [
+            Field::new(f[0]),
+            Field::new(f[1]),
+            Field::new(f[2]),
+            Field::new(f[3]),
+        ]
+    });
+
+    // Palette entries (blue, green, red, reserved) follow the headers
+    let mut palette = Vec::new();
+    if bpp <= 8 {
+        let count = match colours {
+            0 => 1 << bpp,
+            c => c.min(1 << bpp),
+        };
+        let start = FILE_HEADER_LEN + header_len;
+        let table = data
+            .get(start..start + count * 4)
+            .ok_or("BMP palette truncated")?;
+
+        palette = table.chunks(4).map(|c| [c[2], c[1], c[0], 0xff]).collect();
+    }
+
+    // Rows are padded to a multiple of four bytes
+    let stride = (width * bpp as usize).div_ceil(32) * 4;
+    let pixel_data = data
+        .get(offset..offset + stride * height)
+        .ok_or("BMP pixel data truncated")?;
+
+    let mut pixels = Vec::with_capacity(width * height);
+    for y in 0..height {
+        let row = if bottom_up { height - 1 - y } else { y };
+        let row = &pixel_data[row * stride..(row + 1) * stride];
+
+        for x in 0..width {
+            let p = match (bpp, &fields) {
+                (1, _) | (4, _) | (8, _) => {
+                    let bit = x * bpp as usize;
+                    let shift = 8 - bpp as usize - bit % 8;
+                    let index = (row[bit / 8] >> shift) as usize & ((1 << bpp) - 1);
+                    *palette
+                        .get(index)
+                        .ok_or_else(|| format!("invalid BMP palette index: {}", index))?
+                }
+                (24, _) => [row[x * 3 + 2], row[x * 3 + 1], row[x * 3], 0xff],
+                (16, Some(f)) | (32, Some(f)) => {
+                    let b = &row[x * bpp as usize / 8..];
+                    let v = match bpp {
+                        16 => u16::from_le_bytes([b[0], b[1]]) as u32,
+                        _ => u32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+                    };
+                    [
+                        f[0].get(v, 0),
+                        f[1].get(v, 0),
+                        f[2].get(v, 0),
+                        f[3].get(v, 0xff),
+                    ]
+                }
+                _ => unreachable!(),
+            };
+            pixels.push(p);
+        }
+    }
+
+    Ok(Image {
+        width,
+        height,
+        pixels,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a BMP file from a header and pixel data, using a 40 byte info header
+    fn bmp(
+        width: i32,
+        height: i32,
+        bpp: u16,
+        compression: u32,
+        extra: &[u8],
+        pixels: &[u8],
+    ) -> Vec<u8> {
+        let offset = FILE_HEADER_LEN + INFO_HEADER_LEN + extra.len();
+
+        let mut b = Vec::new();
+        b.extend_from_slice(b"BM");
+        b.extend_from_slice(&((offset + pixels.len()) as u32).to_le_bytes());
+        b.extend_from_slice(&[0; 4]);
+        b.extend_from_slice(&(offset as u32).to_le_bytes());
+
+        b.extend_from_slice(&(INFO_HEADER_LEN as u32).to_le_bytes());
+        b.extend_from_slice(&width.to_le_bytes());
+        b.extend_from_slice(&height.to_le_bytes());
+        b.extend_from_slice(&1u16.to_le_bytes());
+        b.extend_from_slice(&bpp.to_le_bytes());
+        b.extend_from_slice(&compression.to_le_bytes());
+        b.extend_from_slice(&[0; 20]);
+
+        b.extend_from_slice(extra);
+        b.extend_from_slice(pixels);
+        b
+    }
+
+    #[test]
+    fn test_load_palette() {
+        // Two colour palette, bottom up rows padded to four bytes
+        let palette = [0, 0, 0, 0, 0xff, 0x80, 0x00, 0];
+        let data = bmp(
+            3,
+            2,
+            1,
+            BI_RGB,
+            &palette,
+            &[0b0100_0000, 0, 0, 0, 0b1010_0000, 0, 0, 0],
+        );
+        let image = load(&data).unwrap();
+
+        let (k, b) = ([0, 0, 0, 0xff], [0x00, 0x80, 0xff, 0xff]);
+        assert_eq!((image.width, image.height), (3, 2));
+        assert_eq!(image.pixels, vec![b, k, b, k, b, k]);
+    }
+
+    #[test]
+    fn test_load_rgb() {
+        // Top down 24 bit rows
+        let pixels = [1, 2, 3, 4, 5, 6, 0, 0, 7, 8, 9, 10, 11, 12, 0, 0];
+        let image = load(&bmp(2, -2, 24, BI_RGB, &[], &pixels)).unwrap();
+        assert_eq!(
+            image.pixels,
+            vec![
+                [3, 2, 1, 0xff],
+                [6, 5, 4, 0xff],
+                [9, 8, 7, 0xff],
+                [12, 11, 10, 0xff]
+            ]
+        );
+
+        // RGB565 bit fields
+        let masks: Vec<u8> = [0xf800u32, 0x07e0, 0x001f]
+            .iter()
+            .flat_map(|m| m.to_le_bytes())
+            .collect();
+        let image = load(&bmp(
+            2,
+            1,
+            16,
+            BI_BITFIELDS,
+            &masks,
+            &[0x00, 0xf8, 0x1f, 0x04],
+        ))
+        .unwrap();
+        assert_eq!(
+            image.pixels,
+            vec![[0xff, 0, 0, 0xff], [0, 0x82, 0xff, 0xff]]
+        );
+
+        assert!(load(&bmp(2, 1, 24, BI_RGB, &[], &[0; 4])).is_err());
+        assert!(load(&bmp(2, 1, 24, 1, &[], &[0; 8])).is_err());
+    }
+}
@@ -0,0 +1,184 @@
+//! Pixel format conversion, including thresholding and dithering for black and white output
+//!
+//! Copyright 2019 Ryan Kurte
+
+use std::str::FromStr;
+
+use super::image::Image;
+
+/// Output pixel format
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PixelFormat {
+    /// Black and white (`PixelBW`), with dark pixels set
+    BW,
+    /// 8-bit greyscale (`PixelG8`)
+    G8,
+    /// 24-bit colour (`PixelRGB24`)
+    RGB24,
+}
+
+impl FromStr for PixelFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bw" => Ok(PixelFormat::BW),
+            "g8" => Ok(PixelFormat::G8),
+            "rgb24" => Ok(PixelFormat::RGB24),
+            _ => Err(format!("Unsupported pixel format: {}", s)),
+        }
+    }
+}
+
+/// Converted pixels, in rows from the top left
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pixels {
+    BW(Vec<bool>),
+    G8(Vec<u8>),
+    RGB24(Vec<[u8; 3]>),
+}
+
+/// Perceptual luminance of a pixel
+pub fn luminance(p: &[u8; 4]) -> u8 {
+    ((p[0] as u32 * 299 + p[1] as u32 * 587 + p[2] as u32 * 114 + 500) / 1000) as u8
+}
+
+/// Convert to black and white, setting pixels darker than the threshold
+pub fn threshold(image: &Image, level: u8) -> Vec<bool> {
+    image.pixels.iter().map(|p| luminance(p) < level).collect()
+}
+
+/// Convert to black and white with Floyd-Steinberg error diffusion, setting dark pixels
+pub fn dither(image: &Image) -> Vec<bool> {
+    let (w, h) = (image.width, image.height);
+    let mut levels: Vec<i32> = image.pixels.iter().map(|p| luminance(p) as i32).collect();
+    let mut set = vec![false; w * h];
+
+    for y in 0..h {
+        for x in 0..w {
+            let i = y * w + x;
+            let old = levels[i];
+            set[i] = old < 128;
+
+            // Spread the quantisation error over the following pixels
+            let err = old - if set[i] { 0 } else { 255 };
+            let mut spread = |dx: isize, dy: usize, weight: i32| {
+                let nx = x as isize + dx;
+                if nx >= 0 && (nx as usize) < w && y + dy < h {
+                    levels[(y + dy) * w + nx as usize] += err * weight / 16;
+                }
+            };
+
+            spread(1, 0, 7);
+            spread(-1, 1, 3);
+            spread(0, 1, 5);
+            spread(1, 1, 1);
+        }
+    }
+
+    set
+}
+
+/// Build a transparency mask, setting pixels with at least half opacity
+pub fn mask(image: &Image) -> Vec<bool> {
+    image.pixels.iter().map(|p| p[3] >= 128).collect()
+}
+
+/// Convert an image to the provided pixel format, with black and white images
+/// thresholded (or dithered) and optionally inverted
+pub fn convert(
+    image: &Image,
+    format: PixelFormat,
+    level: u8,
+    dithered: bool,
+    invert: bool,
+) -> Pixels {
+    match format {
+        PixelFormat::BW => {
+            let mut set = match dithered {
+                true => dither(image),
+                false => threshold(image, level),
+            };
+            if invert {
+                set.iter_mut().for_each(|p| *p = !*p);
+            }
+            Pixels::BW(set)
+        }
+        PixelFormat::G8 => Pixels::G8(image.pixels.iter().map(luminance).collect()),
+        PixelFormat::RGB24 => {
+            Pixels::RGB24(image.pixels.iter().map(|p| [p[0], p[1], p[2]]).collect())
+        }
+    }
+}
+
+/// Split pixels into a palette (in order of first use) and palette indices
+pub fn palette<T: Copy + PartialEq>(pixels: &[T]) -> Result<(Vec<T>, Vec<u8>), String> {
+    let mut palette = Vec::new();
+    let mut indices = Vec::with_capacity(pixels.len());
+
+    for p in pixels {
+        let i = match palette.iter().position(|c| c == p) {
+            Some(i) => i,
+            None => {
+                palette.push(*p);
+                palette.len() - 1
+            }
+        };
+        if i > u8::MAX as usize {
+            return Err(
+                "Too many colours for a palette (maximum 256), use uncompressed output".into(),
+            );
+        }
+        indices.push(i as u8);
+    }
+
+    Ok((palette, indices))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grey(width: usize, height: usize, levels: &[u8]) -> Image {
+        Image {
+            width,
+            height,
+            pixels: levels.iter().map(|&l| [l, l, l, 0xff]).collect(),
+        }
+    }
+
+    #[test]
+    fn test_threshold() {
+        let image = grey(4, 1, &[0, 127, 128, 255]);
+        assert_eq!(threshold(&image, 128), vec![true, true, false, false]);
+
+        assert_eq!(luminance(&[255, 0, 0, 0]), 76);
+        assert_eq!(luminance(&[0, 255, 0, 0]), 150);
+        assert_eq!(
+            convert(&image, PixelFormat::BW, 64, false, true),
+            Pixels::BW(vec![false, true, true, true])
+        );
+    }
+
+    #[test]
+    fn test_dither() {
+        // Dithered coverage follows the grey level (75% for 64), less error diffused
+        // past the image edges
+        let image = grey(8, 8, &[64; 64]);
+        let count = dither(&image).iter().filter(|&&p| p).count();
+        assert!((46..=50).contains(&count), "{} pixels set", count);
+
+        let image = grey(8, 8, &[255; 64]);
+        assert!(dither(&image).iter().all(|&p| !p));
+    }
+
+    #[test]
+    fn test_palette() {
+        let (palette, indices) = palette(&[5u8, 7, 5, 9, 7]).unwrap();
+        assert_eq!(palette, vec![5, 7, 9]);
+        assert_eq!(indices, vec![0, 1, 0, 2, 1]);
+
+        let pixels: Vec<u16> = (0..300).collect();
+        assert!(super::palette(&pixels).is_err());
+    }
+}
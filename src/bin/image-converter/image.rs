@@ -0,0 +1,71 @@
+//! Source image loading, decoding PNG and BMP files into RGBA pixels
+//!
+//! Copyright 2019 Ryan Kurte
+
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::Path;
+
+use png::{ColorType, Transformations};
+
+use super::bmp;
+
+/// Decoded image, with RGBA pixels in rows from the top left
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<[u8; 4]>,
+}
+
+/// Load a PNG image, expanding all colour types and bit depths to 8 bit RGBA
+fn load_png(path: &Path) -> Result<Image, String> {
+    let f = BufReader::new(File::open(path).map_err(|e| e.to_string())?);
+
+    let mut decoder = png::Decoder::new(f);
+    decoder.set_transformations(Transformations::EXPAND | Transformations::STRIP_16);
+
+    let (_, mut reader) = decoder.read_info().map_err(|e| e.to_string())?;
+    let (width, height) = reader.info().size();
+    let (width, height) = (width as usize, height as usize);
+    let (colour, _) = reader.output_color_type();
+
+    let mut data = vec![0; reader.output_buffer_size()];
+    reader.next_frame(&mut data).map_err(|e| e.to_string())?;
+
+    let channels = data.len() / (width * height).max(1);
+    let pixels = data
+        .chunks(channels)
+        .take(width * height)
+        .map(|c| match colour {
+            ColorType::Grayscale => [c[0], c[0], c[0], 0xff],
+            ColorType::GrayscaleAlpha => [c[0], c[0], c[0], c[1]],
+            ColorType::RGB => [c[0], c[1], c[2], 0xff],
+            _ => [c[0], c[1], c[2], c[3]],
+        })
+        .collect();
+
+    Ok(Image {
+        width,
+        height,
+        pixels,
+    })
+}
+
+/// Load an image, with the format (PNG or BMP) selected by extension
+pub fn load(path: &str) -> Result<Image, String> {
+    let path = Path::new(path);
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase());
+
+    match extension.as_ref().map(|e| e.as_str()) {
+        Some("png") => load_png(path),
+        Some("bmp") => bmp::load(&fs::read(path).map_err(|e| e.to_string())?),
+        _ => Err(format!(
+            "Unsupported image format: {} (expected .png or .bmp)",
+            path.display()
+        )),
+    }
+}
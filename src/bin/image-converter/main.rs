@@ -0,0 +1,481 @@
+//! Image converter imports PNG/BMP images into micro-gui sprites
+//! (as rust source or binary sprite data)
+//!
+//! Copyright 2019 Ryan Kurte
+
+use std::fmt::Write as FmtWrite;
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use micro_gui::graphics::rle;
+
+extern crate structopt;
+use structopt::StructOpt;
+
+mod bmp;
+mod convert;
+mod image;
+
+use convert::{PixelFormat, Pixels};
+
+#[derive(Debug, Clone, StructOpt)]
+pub struct Config {
+    #[structopt(short = "i", long = "image")]
+    /// Image to be converted (PNG or BMP, selected by file extension)
+    pub image: String,
+
+    #[structopt(short = "o", long = "output", default_value = "./output.rs")]
+    /// Output sprite file
+    pub output: String,
+
+    #[structopt(long = "format", default_value = "rust")]
+    /// Output format (rust or binary)
+    pub format: Format,
+
+    #[structopt(short = "p", long = "pixel", default_value = "bw")]
+    /// Output pixel format (bw, g8 or rgb24)
+    pub pixel: PixelFormat,
+
+    #[structopt(short = "n", long = "name")]
+    /// Name for the generated sprite objects (defaults to one derived from the image file)
+    pub name: Option<String>,
+
+    #[structopt(long = "threshold", default_value = "128")]
+    /// Luminance (0 to 255) below which a pixel is set, for black and white output
+    pub threshold: u8,
+
+    #[structopt(long = "dither")]
+    /// Dither black and white output rather than thresholding
+    pub dither: bool,
+
+    #[structopt(long = "invert")]
+    /// Invert black and white output, setting light pixels rather than dark pixels
+    pub invert: bool,
+
+    #[structopt(long = "mask")]
+    /// Generate a transparency mask from the image alpha channel
+    pub mask: bool,
+
+    #[structopt(long = "compress")]
+    /// Run-length encode the output (using a palette for greyscale and colour output)
+    pub compress: bool,
+}
+
+/// Output format for converted sprites
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    /// Rust source defining static sprite data
+    Rust,
+    /// Binary sprite data, with any palette and mask in separate files
+    Binary,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rust" => Ok(Format::Rust),
+            "binary" => Ok(Format::Binary),
+            _ => Err(format!("Unsupported output format: {}", s)),
+        }
+    }
+}
+
+/// Sprite data, either raw pixels or run-length encoded
+#[derive(Debug, Clone, PartialEq)]
+enum Data {
+    /// Uncompressed pixels, for use with `Bitmap`
+    Raw(Pixels),
+    /// Run-length encoded black and white pixels, for use with `RleBW`
+    RleBW(Vec<u8>),
+    /// Palette and run-length encoded indices, for use with `RlePalette`
+    RlePalette(Pixels, Vec<u8>),
+}
+
+impl Data {
+    /// Encode converted pixels, optionally compressed
+    fn encode(pixels: Pixels, compress: bool) -> Result<Data, String> {
+        if !compress {
+            return Ok(Data::Raw(pixels));
+        }
+
+        let data = match pixels {
+            Pixels::BW(p) => Data::RleBW(rle::encode_bw(&p)),
+            Pixels::G8(p) => {
+                let (palette, indices) = convert::palette(&p)?;
+                Data::RlePalette(Pixels::G8(palette), rle::encode_palette(&indices))
+            }
+            Pixels::RGB24(p) => {
+                let (palette, indices) = convert::palette(&p)?;
+                Data::RlePalette(Pixels::RGB24(palette), rle::encode_palette(&indices))
+            }
+        };
+
+        Ok(data)
+    }
+
+    /// Describe how to construct a sprite from the generated objects, with the size
+    /// constants named by `name` and the data by `data`
+    fn usage(&self, name: &str, data: &str) -> String {
+        let size = format!("{}_WIDTH, {}_HEIGHT", name, name);
+        match self {
+            Data::Raw(_) => format!("Bitmap::new({}, &{})", size, data),
+            Data::RleBW(_) => format!("RleBW::parse({}, &{}).unwrap()", size, data),
+            Data::RlePalette(..) => {
+                format!(
+                    "RlePalette::parse({}, &{}_PALETTE, &{}).unwrap()",
+                    size, data, data
+                )
+            }
+        }
+    }
+}
+
+/// Maximum line width used to lay out data arrays
+const MAX_WIDTH: usize = 100;
+
+/// Generate a rust static array, filling lines with as many items as fit
+fn write_array(s: &mut String, name: &str, kind: &str, items: &[String]) {
+    let line = format!(
+        "pub static {}: [{}; {}] = [{}];",
+        name,
+        kind,
+        items.len(),
+        items.join(", ")
+    );
+    if line.len() <= MAX_WIDTH {
+        writeln!(s, "{}", line).unwrap();
+        return;
+    }
+
+    writeln!(s, "pub static {}: [{}; {}] = [", name, kind, items.len()).unwrap();
+
+    let mut line = String::new();
+    for i in items {
+        if !line.is_empty() && 4 + line.len() + i.len() + 2 > MAX_WIDTH {
+            writeln!(s, "    {}", line.trim_end()).unwrap();
+            line.clear();
+        }
+        write!(line, "{}, ", i).unwrap();
+    }
+    if !line.is_empty() {
+        writeln!(s, "    {}", line.trim_end()).unwrap();
+    }
+
+    writeln!(s, "];").unwrap();
+}
+
+/// Generate a rust static array of pixels
+fn write_pixels(s: &mut String, name: &str, pixels: &Pixels) {
+    let (kind, items): (_, Vec<_>) = match pixels {
+        Pixels::BW(p) => ("bool", p.iter().map(|p| p.to_string()).collect()),
+        Pixels::G8(p) => (
+            "PixelG8",
+            p.iter().map(|p| format!("PixelG8({})", p)).collect(),
+        ),
+        Pixels::RGB24(p) => (
+            "PixelRGB24",
+            p.iter()
+                .map(|p| format!("PixelRGB24 {{ r: {}, g: {}, b: {} }}", p[0], p[1], p[2]))
+                .collect(),
+        ),
+    };
+
+    write_array(s, name, kind, &items);
+}
+
+/// Generate a rust static array of encoded bytes
+fn write_bytes(s: &mut String, name: &str, bytes: &[u8]) {
+    let items: Vec<_> = bytes.iter().map(|b| format!("0x{:02x}", b)).collect();
+    write_array(s, name, "u8", &items);
+}
+
+fn write_data(s: &mut String, name: &str, data: &Data) {
+    match data {
+        Data::Raw(p) => write_pixels(s, name, p),
+        Data::RleBW(b) => write_bytes(s, name, b),
+        Data::RlePalette(p, b) => {
+            write_pixels(s, &format!("{}_PALETTE", name), p);
+            write_bytes(s, name, b);
+        }
+    }
+}
+
+/// Generate rust source for a converted sprite
+fn generate(
+    name: &str,
+    source: &str,
+    size: (usize, usize),
+    format: PixelFormat,
+    data: &Data,
+    mask: Option<&Data>,
+) -> String {
+    let mut s = String::new();
+    let mask_name = format!("{}_MASK", name);
+
+    writeln!(s, "//! {} {}x{} sprite", source, size.0, size.1).unwrap();
+    writeln!(s, "//!").unwrap();
+    writeln!(s, "//! Sprite: `{}`", data.usage(name, name)).unwrap();
+    if let Some(m) = mask {
+        writeln!(s, "//! Mask: `{}`", m.usage(name, &mask_name)).unwrap();
+    }
+    writeln!(s, "//!").unwrap();
+    writeln!(s, "//! Generated by micro-gui image-converter").unwrap();
+    writeln!(s).unwrap();
+
+    match format {
+        PixelFormat::BW => (),
+        PixelFormat::G8 => writeln!(s, "use micro_gui::types::pixel::PixelG8;\n").unwrap(),
+        PixelFormat::RGB24 => writeln!(s, "use micro_gui::types::pixel::PixelRGB24;\n").unwrap(),
+    }
+
+    writeln!(s, "pub const {}_WIDTH: usize = {};", name, size.0).unwrap();
+    writeln!(s, "pub const {}_HEIGHT: usize = {};", name, size.1).unwrap();
+    writeln!(s).unwrap();
+    write_data(&mut s, name, data);
+
+    if let Some(m) = mask {
+        writeln!(s).unwrap();
+        write_data(&mut s, &mask_name, m);
+    }
+
+    s
+}
+
+/// Binary form of raw pixels, matching the `Buffer` layout for each pixel format
+/// (with black and white rows padded to whole bytes)
+fn pixel_bytes(width: usize, pixels: &Pixels) -> Vec<u8> {
+    match pixels {
+        Pixels::BW(p) => p
+            .chunks(width.max(1))
+            .flat_map(|row| {
+                row.chunks(8).map(|c| {
+                    c.iter()
+                        .enumerate()
+                        .fold(0u8, |b, (i, &p)| b | (p as u8) << (7 - i))
+                })
+            })
+            .collect(),
+        Pixels::G8(p) => p.clone(),
+        Pixels::RGB24(p) => p.iter().flatten().cloned().collect(),
+    }
+}
+
+/// Binary form of sprite data, and of the palette for palette sprites
+fn binary(width: usize, data: &Data) -> (Vec<u8>, Option<Vec<u8>>) {
+    match data {
+        Data::Raw(p) => (pixel_bytes(width, p), None),
+        Data::RleBW(b) => (b.clone(), None),
+        Data::RlePalette(p, b) => (b.clone(), Some(pixel_bytes(width, p))),
+    }
+}
+
+/// Build a rust static name from an image name
+fn static_name(image_name: &str) -> String {
+    image_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn write_file(path: &str, data: &[u8]) -> Result<(), String> {
+    let mut f = File::create(path).map_err(|e| e.to_string())?;
+    f.write_all(data).map_err(|e| e.to_string())
+}
+
+fn main() -> Result<(), String> {
+    let config = Config::from_args();
+
+    let image_name = PathBuf::from(&config.image)
+        .file_stem()
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_owned();
+
+    let image = image::load(&config.image)?;
+    let size = (image.width, image.height);
+
+    let pixels = convert::convert(
+        &image,
+        config.pixel,
+        config.threshold,
+        config.dither,
+        config.invert,
+    );
+    let data = Data::encode(pixels, config.compress)?;
+
+    let mask = match config.mask {
+        true => Some(Data::encode(
+            Pixels::BW(convert::mask(&image)),
+            config.compress,
+        )?),
+        false => None,
+    };
+
+    let name = match &config.name {
+        Some(n) => n.clone(),
+        None => static_name(&image_name),
+    };
+
+    match config.format {
+        Format::Rust => {
+            let s = generate(&name, &image_name, size, config.pixel, &data, mask.as_ref());
+            write_file(&config.output, s.as_bytes())?;
+        }
+        Format::Binary => {
+            // Palette and mask data are written alongside the sprite data
+            let (bytes, palette) = binary(image.width, &data);
+            write_file(&config.output, &bytes)?;
+
+            if let Some(p) = palette {
+                write_file(&format!("{}.pal", config.output), &p)?;
+            }
+            if let Some(m) = &mask {
+                write_file(
+                    &format!("{}.mask", config.output),
+                    &binary(image.width, m).0,
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use micro_gui::graphics::rle::{RleBW, RlePalette};
+    use micro_gui::graphics::Sprite;
+    use micro_gui::types::pixel::PixelG8;
+
+    #[test]
+    fn test_pixel_bytes() {
+        // Black and white rows are packed most significant bit first and padded to bytes
+        let mut pixels = vec![false; 20];
+        for &i in &[0, 1, 8, 9, 19] {
+            pixels[i] = true;
+        }
+        assert_eq!(
+            pixel_bytes(10, &Pixels::BW(pixels)),
+            vec![0b1100_0000, 0b1100_0000, 0b0000_0000, 0b0100_0000]
+        );
+
+        assert_eq!(
+            pixel_bytes(2, &Pixels::RGB24(vec![[1, 2, 3], [4, 5, 6]])),
+            vec![1, 2, 3, 4, 5, 6]
+        );
+    }
+
+    #[test]
+    fn test_write_array() {
+        let mut s = String::new();
+        let items: Vec<_> = (0..40).map(|i| format!("0x{:02x}", i)).collect();
+        write_array(&mut s, "LONG", "u8", &items);
+
+        // Long arrays are wrapped to fill lines up to the maximum width
+        let lines: Vec<_> = s.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[0], "pub static LONG: [u8; 40] = [");
+        assert_eq!(lines[1].len(), 99);
+        assert!(lines[1].starts_with("    0x00, 0x01,") && lines[1].ends_with(" 0x0f,"));
+        assert!(lines[2].starts_with("    0x10,") && lines[2].ends_with(" 0x1f,"));
+        assert_eq!(
+            lines[3],
+            "    0x20, 0x21, 0x22, 0x23, 0x24, 0x25, 0x26, 0x27,"
+        );
+        assert_eq!(lines[4], "];");
+    }
+
+    #[test]
+    fn test_generate() {
+        let data = Data::encode(Pixels::BW(vec![true, false, false, true]), false).unwrap();
+        let s = generate("DOT", "dot", (2, 2), PixelFormat::BW, &data, None);
+        let expected = [
+            "//! dot 2x2 sprite",
+            "//!",
+            "//! Sprite: `Bitmap::new(DOT_WIDTH, DOT_HEIGHT, &DOT)`",
+            "//!",
+            "//! Generated by micro-gui image-converter",
+            "",
+            "pub const DOT_WIDTH: usize = 2;",
+            "pub const DOT_HEIGHT: usize = 2;",
+            "",
+            "pub static DOT: [bool; 4] = [true, false, false, true];",
+        ];
+        assert_eq!(s.lines().collect::<Vec<_>>(), expected);
+
+        let data = Data::encode(Pixels::BW(vec![true; 300]), true).unwrap();
+        let mask = Data::encode(Pixels::BW(vec![false; 300]), true).unwrap();
+        let s = generate("BAR", "bar", (30, 10), PixelFormat::BW, &data, Some(&mask));
+        let expected = [
+            "//! bar 30x10 sprite",
+            "//!",
+            "//! Sprite: `RleBW::parse(BAR_WIDTH, BAR_HEIGHT, &BAR).unwrap()`",
+            "//! Mask: `RleBW::parse(BAR_WIDTH, BAR_HEIGHT, &BAR_MASK).unwrap()`",
+            "//!",
+            "//! Generated by micro-gui image-converter",
+            "",
+            "pub const BAR_WIDTH: usize = 30;",
+            "pub const BAR_HEIGHT: usize = 10;",
+            "",
+            "pub static BAR: [u8; 3] = [0xff, 0xff, 0xab];",
+            "",
+            "pub static BAR_MASK: [u8; 3] = [0x7f, 0x7f, 0x2b];",
+        ];
+        assert_eq!(s.lines().collect::<Vec<_>>(), expected);
+
+        let data = Data::encode(Pixels::G8(vec![0, 0, 255, 128, 128, 128]), true).unwrap();
+        let s = generate("GREY", "grey", (3, 2), PixelFormat::G8, &data, None);
+        let expected = [
+            "//! grey 3x2 sprite",
+            "//!",
+            "//! Sprite: `RlePalette::parse(GREY_WIDTH, GREY_HEIGHT, &GREY_PALETTE, &GREY).unwrap()`",
+            "//!",
+            "//! Generated by micro-gui image-converter",
+            "",
+            "use micro_gui::types::pixel::PixelG8;",
+            "",
+            "pub const GREY_WIDTH: usize = 3;",
+            "pub const GREY_HEIGHT: usize = 2;",
+            "",
+            "pub static GREY_PALETTE: [PixelG8; 3] = [PixelG8(0), PixelG8(255), PixelG8(128)];",
+            "pub static GREY: [u8; 6] = [0x00, 0x01, 0x01, 0x00, 0x02, 0x02];",
+        ];
+        assert_eq!(s.lines().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn test_binary_rle() {
+        let (w, h) = (10, 3);
+
+        let pixels: Vec<bool> = (0..w * h).map(|i| i % 7 < 3).collect();
+        let (bytes, palette) = binary(w, &Data::encode(Pixels::BW(pixels.clone()), true).unwrap());
+        assert_eq!(palette, None);
+
+        let mut sprite = RleBW::parse(w, h, &bytes).unwrap();
+        for (i, p) in pixels.iter().enumerate() {
+            assert_eq!(sprite.get(i % w, i / w), p, "pixel {}", i);
+        }
+
+        let levels: Vec<u8> = (0..w * h).map(|i| (i / 4 * 30) as u8).collect();
+        let (bytes, palette) = binary(w, &Data::encode(Pixels::G8(levels.clone()), true).unwrap());
+        let palette: Vec<_> = palette.unwrap().into_iter().map(PixelG8).collect();
+
+        let mut sprite = RlePalette::parse(w, h, &palette, &bytes).unwrap();
+        for (i, &l) in levels.iter().enumerate() {
+            assert_eq!(sprite.get(i % w, i / w), &PixelG8(l), "pixel {}", i);
+        }
+    }
+}
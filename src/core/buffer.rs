@@ -6,7 +6,7 @@ use std::*;
 
 use std::marker::PhantomData;
 
-use crate::types::pixel::{PixelBW, PixelRGB24};
+use crate::types::pixel::{PixelBW, PixelG8, PixelRGB24};
 
 /// Buff trait encompasses methods required for a graphics buffer
 pub trait Buff<Pixel> {
@@ -18,6 +18,112 @@ pub trait Buff<Pixel> {
     fn size(&self) -> (usize, usize);
     /// Clear clears the buffer
     fn clear(&mut self, p: &Pixel);
+
+    /// Copy a region of the buffer to another position, handling overlapping regions
+    /// Regions are limited to the buffer size.
+    fn copy_within(&mut self, from: (usize, usize), to: (usize, usize), size: (usize, usize)) {
+        copy_pixels(self, from, to, size);
+    }
+
+    /// Copy a region from another buffer to a position in this buffer
+    /// Regions are limited to the sizes of both buffers.
+    fn blit(
+        &mut self,
+        src: &dyn Buff<Pixel>,
+        from: (usize, usize),
+        to: (usize, usize),
+        size: (usize, usize),
+    ) {
+        let (w, h) = copy_size(src.size(), self.size(), from, to, size);
+
+        for y in 0..h {
+            for x in 0..w {
+                self.set(to.0 + x, to.1 + y, &src.get(from.0 + x, from.1 + y));
+            }
+        }
+    }
+
+    /// Scroll a region of the buffer by the provided offset, filling the exposed area
+    fn scroll(
+        &mut self,
+        origin: (usize, usize),
+        size: (usize, usize),
+        dx: isize,
+        dy: isize,
+        p: &Pixel,
+    ) {
+        let (x, y) = origin;
+        let (w, h) = copy_size(self.size(), self.size(), origin, origin, size);
+        let (ax, ay) = (dx.unsigned_abs().min(w), dy.unsigned_abs().min(h));
+
+        // Move the remaining content
+        let from = (
+            if dx < 0 { x + ax } else { x },
+            if dy < 0 { y + ay } else { y },
+        );
+        let to = (
+            if dx > 0 { x + ax } else { x },
+            if dy > 0 { y + ay } else { y },
+        );
+        self.copy_within(from, to, (w - ax, h - ay));
+
+        // Fill the exposed rows, then the exposed columns of the remaining rows
+        let rows = if dy > 0 { y..y + ay } else { y + h - ay..y + h };
+        let cols = if dx > 0 { x..x + ax } else { x + w - ax..x + w };
+
+        for py in y..y + h {
+            let exposed = if rows.contains(&py) {
+                x..x + w
+            } else {
+                cols.clone()
+            };
+            for px in exposed {
+                self.set(px, py, p);
+            }
+        }
+    }
+}
+
+/// Limit the size of a copy between positions in two buffers to fit within both
+fn copy_size(
+    src: (usize, usize),
+    dst: (usize, usize),
+    from: (usize, usize),
+    to: (usize, usize),
+    size: (usize, usize),
+) -> (usize, usize) {
+    (
+        size.0
+            .min(src.0.saturating_sub(from.0))
+            .min(dst.0.saturating_sub(to.0)),
+        size.1
+            .min(src.1.saturating_sub(from.1))
+            .min(dst.1.saturating_sub(to.1)),
+    )
+}
+
+/// Order of rows or columns for copying within a buffer, stepping away from the destination
+/// so overlapping source pixels are read before they are overwritten
+fn copy_order(from: usize, to: usize, len: usize) -> impl Iterator<Item = usize> {
+    let reverse = to > from;
+    (0..len).map(move |i| if reverse { len - 1 - i } else { i })
+}
+
+/// Copy a region within a buffer pixel by pixel
+fn copy_pixels<Pixel, B: Buff<Pixel> + ?Sized>(
+    b: &mut B,
+    from: (usize, usize),
+    to: (usize, usize),
+    size: (usize, usize),
+) {
+    let (w, h) = copy_size(b.size(), b.size(), from, to, size);
+
+    for y in copy_order(from.1, to.1, h) {
+        for x in copy_order(from.0, to.0, w) {
+            let p = b.get(from.0 + x, from.1 + y);
+            b.set(to.0 + x, to.1 + y, &p);
+        }
+    }
 }
 
 /// Buffer implements a generic display buffer over an arbitrary pixel type
@@ -58,6 +164,40 @@ impl<'a> Buffer<'a, PixelBW> {
             _pixel: PhantomData,
         };
     }
+    /// Split a row of pixels into leading pixels, whole bytes and trailing pixels
+    fn split_row(x: usize, w: usize) -> (usize, usize, usize) {
+        let lead = ((8 - x % 8) % 8).min(w);
+        let bytes = (w - lead) / 8;
+        (lead, bytes, w - lead - bytes * 8)
+    }
+
+    /// Copy a region from another black and white buffer, copying whole bytes where the
+    /// source and destination are aligned
+    pub fn copy_from(
+        &mut self,
+        src: &Buffer<PixelBW>,
+        from: (usize, usize),
+        to: (usize, usize),
+        size: (usize, usize),
+    ) {
+        if from.0 % 8 != to.0 % 8 {
+            return self.blit(src, from, to, size);
+        }
+
+        let (w, h) = copy_size(src.size(), self.size(), from, to, size);
+        let (lead, bytes, trail) = Self::split_row(from.0, w);
+
+        for y in 0..h {
+            let (fy, ty) = (from.1 + y, to.1 + y);
+            for x in (0..lead).chain(w - trail..w) {
+                self.set(to.0 + x, ty, &src.get(from.0 + x, fy));
+            }
+
+            let s = src.line_width_bytes * fy + src.porch_bytes + (from.0 + lead) / 8;
+            let d = self.line_width_bytes * ty + self.porch_bytes + (to.0 + lead) / 8;
+            self.data[d..d + bytes].copy_from_slice(&src.data[s..s + bytes]);
+        }
+    }
 }
 
 impl<'a> Buff<PixelBW> for Buffer<'a, PixelBW> {
@@ -94,6 +234,43 @@ impl<'a> Buff<PixelBW> for Buffer<'a, PixelBW> {
         }
     }
 
+    /// Black and White mode copy, moving whole bytes where the source and destination are aligned
+    fn copy_within(&mut self, from: (usize, usize), to: (usize, usize), size: (usize, usize)) {
+        if from.0 % 8 != to.0 % 8 {
+            return copy_pixels(self, from, to, size);
+        }
+
+        let (w, h) = copy_size(self.size(), self.size(), from, to, size);
+        let (lead, bytes, trail) = Self::split_row(from.0, w);
+
+        for y in copy_order(from.1, to.1, h) {
+            let (fy, ty) = (from.1 + y, to.1 + y);
+
+            let edge = |b: &mut Self, x: usize| {
+                let p = b.get(from.0 + x, fy);
+                b.set(to.0 + x, ty, &p);
+            };
+
+            // Pixels are copied starting from the end furthest in the direction of movement
+            let right = to.0 > from.0;
+            if right {
+                (w - trail..w).rev().for_each(|x| edge(self, x));
+            } else {
+                (0..lead).for_each(|x| edge(self, x));
+            }
+
+            let s = self.line_width_bytes * fy + self.porch_bytes + (from.0 + lead) / 8;
+            let d = self.line_width_bytes * ty + self.porch_bytes + (to.0 + lead) / 8;
+            self.data.copy_within(s..s + bytes, d);
+
+            if right {
+                (0..lead).rev().for_each(|x| edge(self, x));
+            } else {
+                (w - trail..w).for_each(|x| edge(self, x));
+            }
+        }
+    }
+
     /// Fetch the buffer size in pixels
     fn size(&self) -> (usize, usize) {
         return (self.width, self.height);
@@ -120,6 +297,22 @@ impl<'a> Buffer<'a, PixelRGB24> {
             _pixel: PhantomData,
         };
     }
+    /// Copy a region from another RGB24 buffer, copying whole rows of bytes
+    pub fn copy_from(
+        &mut self,
+        src: &Buffer<PixelRGB24>,
+        from: (usize, usize),
+        to: (usize, usize),
+        size: (usize, usize),
+    ) {
+        let (w, h) = copy_size(src.size(), self.size(), from, to, size);
+
+        for y in 0..h {
+            let s = src.line_width_bytes * (from.1 + y) + from.0 * 3 + src.porch_bytes;
+            let d = self.line_width_bytes * (to.1 + y) + to.0 * 3 + self.porch_bytes;
+            self.data[d..d + w * 3].copy_from_slice(&src.data[s..s + w * 3]);
+        }
+    }
 }
 
 impl<'a> Buff<PixelRGB24> for Buffer<'a, PixelRGB24> {
@@ -152,6 +345,67 @@ impl<'a> Buff<PixelRGB24> for Buffer<'a, PixelRGB24> {
         }
     }
 
+    /// RGB24 mode copy, moving whole rows of bytes
+    fn copy_within(&mut self, from: (usize, usize), to: (usize, usize), size: (usize, usize)) {
+        let (w, h) = copy_size(self.size(), self.size(), from, to, size);
+
+        for y in copy_order(from.1, to.1, h) {
+            let s = self.line_width_bytes * (from.1 + y) + from.0 * 3 + self.porch_bytes;
+            let d = self.line_width_bytes * (to.1 + y) + to.0 * 3 + self.porch_bytes;
+            self.data.copy_within(s..s + w * 3, d);
+        }
+    }
+
+    /// Fetch the buffer size in pixels
+    fn size(&self) -> (usize, usize) {
+        return (self.width, self.height);
+    }
+}
+
+impl<'a> Buffer<'a, PixelG8> {
+    // Create a new 8-bit grey-scale buffer
+    pub fn new(
+        width: usize,
+        height: usize,
+        porch_bytes: usize,
+        trailer_bytes: usize,
+        data: &'a mut [u8],
+    ) -> Self {
+        let line_width_bytes = porch_bytes + width + trailer_bytes;
+        return Self {
+            width,
+            height,
+            porch_bytes,
+            trailer_bytes,
+            line_width_bytes,
+            data,
+            _pixel: PhantomData,
+        };
+    }
+}
+
+impl<'a> Buff<PixelG8> for Buffer<'a, PixelG8> {
+    /// G8 mode pixel set function
+    fn set(&mut self, x: usize, y: usize, p: &PixelG8) {
+        let index: usize = self.line_width_bytes * y + x + self.porch_bytes;
+        self.data[index] = p.0;
+    }
+
+    /// G8 mode pixel get function
+    fn get(&self, x: usize, y: usize) -> PixelG8 {
+        let index: usize = self.line_width_bytes * y + x + self.porch_bytes;
+        PixelG8(self.data[index])
+    }
+
+    /// G8 mode buffer clear function
+    fn clear(&mut self, p: &PixelG8) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                self.set(x, y, p);
+            }
+        }
+    }
+
     /// Fetch the buffer size in pixels
     fn size(&self) -> (usize, usize) {
         return (self.width, self.height);
@@ -189,6 +443,7 @@ mod tests {
     const TRAILER: usize = 1;
     const SIZE_BW: usize = (PORCH + WIDTH / 8 + TRAILER) * HEIGHT;
     const SIZE_RGB24: usize = (PORCH + WIDTH * 3 + TRAILER) * HEIGHT;
+    const SIZE_G8: usize = (PORCH + WIDTH + TRAILER) * HEIGHT;
 
     #[test]
     fn test_bw() {
@@ -246,6 +501,170 @@ mod tests {
         assert_eq!(buffer.data, blank_slice);
     }
 
+    #[test]
+    fn test_g8() {
+        let mut data = [0u8; SIZE_G8];
+        let mut buffer = Buffer::<PixelG8>::new(WIDTH, HEIGHT, PORCH, TRAILER, &mut data);
+
+        buffer.set(1, 1, &PixelG8(0x80));
+        assert_eq!(buffer.get(1, 1), PixelG8(0x80));
+        assert_eq!(buffer.data[PORCH + WIDTH + TRAILER + PORCH + 1], 0x80);
+
+        buffer.clear(&PixelG8::black());
+        assert!(buffer.data.iter().enumerate().all(|(i, d)| {
+            let x = i % (PORCH + WIDTH + TRAILER);
+            *d == if x >= PORCH && x < PORCH + WIDTH {
+                0xff
+            } else {
+                0x00
+            }
+        }));
+    }
+
+    /// Fill a buffer with a pseudo-random pattern
+    fn scramble<P, B: Buff<P>>(b: &mut B, f: impl Fn(u32) -> P) {
+        let (w, h) = b.size();
+        let mut seed = 0x1234_5678u32;
+        for y in 0..h {
+            for x in 0..w {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                b.set(x, y, &f(seed >> 16));
+            }
+        }
+    }
+
+    /// Fetch all pixels in a buffer
+    fn pixels<P, B: Buff<P>>(b: &B) -> Vec<Vec<P>> {
+        let (w, h) = b.size();
+        (0..h)
+            .map(|y| (0..w).map(|x| b.get(x, y)).collect())
+            .collect()
+    }
+
+    /// Check a copy within a buffer against copying from a snapshot of its contents
+    fn check_copy<P, B>(b: &mut B, from: (usize, usize), to: (usize, usize), size: (usize, usize))
+    where
+        P: Clone + PartialEq + fmt::Debug,
+        B: Buff<P>,
+    {
+        let mut expected = pixels(b);
+        let (w, h) = b.size();
+        for y in 0..size.1 {
+            for x in 0..size.0 {
+                let (fx, fy, tx, ty) = (from.0 + x, from.1 + y, to.0 + x, to.1 + y);
+                if fx < w && fy < h && tx < w && ty < h {
+                    expected[ty][tx] = pixels(b)[fy][fx].clone();
+                }
+            }
+        }
+
+        b.copy_within(from, to, size);
+        assert_eq!(
+            pixels(b),
+            expected,
+            "copy {:?} -> {:?} ({:?})",
+            from,
+            to,
+            size
+        );
+    }
+
+    const COPIES: &[((usize, usize), (usize, usize), (usize, usize))] = &[
+        ((0, 0), (8, 2), (8, 4)),
+        ((3, 1), (11, 1), (13, 6)),
+        ((11, 2), (3, 2), (13, 6)),
+        ((3, 5), (3, 2), (9, 11)),
+        ((2, 2), (3, 3), (7, 7)),
+        ((5, 4), (1, 2), (20, 20)),
+        ((1, 1), (9, 0), (2, 2)),
+    ];
+
+    #[test]
+    fn test_copy_within() {
+        for &(from, to, size) in COPIES {
+            let mut data = [0u8; SIZE_BW];
+            let mut buffer = Buffer::<PixelBW>::new(WIDTH, HEIGHT, PORCH, TRAILER, &mut data);
+            scramble(&mut buffer, |r| r & 1 != 0);
+            check_copy(&mut buffer, from, to, size);
+
+            let mut data = [0u8; SIZE_RGB24];
+            let mut buffer = Buffer::<PixelRGB24>::new(WIDTH, HEIGHT, PORCH, TRAILER, &mut data);
+            scramble(&mut buffer, PixelRGB24::from_hex);
+            check_copy(&mut buffer, from, to, size);
+
+            let mut data = [0u8; SIZE_G8];
+            let mut buffer = Buffer::<PixelG8>::new(WIDTH, HEIGHT, PORCH, TRAILER, &mut data);
+            scramble(&mut buffer, |r| PixelG8(r as u8));
+            check_copy(&mut buffer, from, to, size);
+        }
+    }
+
+    #[test]
+    fn test_copy_from() {
+        for &(from, to, size) in COPIES {
+            let mut src_data = [0u8; SIZE_BW];
+            let mut src = Buffer::<PixelBW>::new(WIDTH, HEIGHT, PORCH, TRAILER, &mut src_data);
+            scramble(&mut src, |r| r & 1 != 0);
+
+            let mut fast_data = [0u8; SIZE_BW];
+            let mut fast = Buffer::<PixelBW>::new(WIDTH, HEIGHT, PORCH, TRAILER, &mut fast_data);
+            fast.copy_from(&src, from, to, size);
+
+            let mut slow_data = [0u8; SIZE_BW];
+            let mut slow = Buffer::<PixelBW>::new(WIDTH, HEIGHT, PORCH, TRAILER, &mut slow_data);
+            slow.blit(&src, from, to, size);
+            assert_eq!(pixels(&fast), pixels(&slow));
+
+            let mut src_data = [0u8; SIZE_RGB24];
+            let mut src = Buffer::<PixelRGB24>::new(WIDTH, HEIGHT, PORCH, TRAILER, &mut src_data);
+            scramble(&mut src, PixelRGB24::from_hex);
+
+            let mut fast_data = [0u8; SIZE_RGB24];
+            let mut fast = Buffer::<PixelRGB24>::new(WIDTH, HEIGHT, PORCH, TRAILER, &mut fast_data);
+            fast.copy_from(&src, from, to, size);
+
+            let mut slow_data = [0u8; SIZE_RGB24];
+            let mut slow = Buffer::<PixelRGB24>::new(WIDTH, HEIGHT, PORCH, TRAILER, &mut slow_data);
+            slow.blit(&src, from, to, size);
+            assert_eq!(pixels(&fast), pixels(&slow));
+        }
+    }
+
+    #[test]
+    fn test_scroll() {
+        let mut data = [0u8; SIZE_G8];
+        let mut buffer = Buffer::<PixelG8>::new(WIDTH, HEIGHT, PORCH, TRAILER, &mut data);
+        for y in 0..HEIGHT {
+            for x in 0..WIDTH {
+                buffer.set(x, y, &PixelG8((y * 16 + x) as u8));
+            }
+        }
+
+        // Scroll a 4x3 region up by one and right by two
+        buffer.scroll((2, 2), (4, 3), 2, -1, &PixelG8(0xff));
+        let region: Vec<Vec<u8>> = (2..5)
+            .map(|y| (2..6).map(|x| buffer.get(x, y).0).collect())
+            .collect();
+        assert_eq!(
+            region,
+            vec![
+                vec![0xff, 0xff, 0x32, 0x33],
+                vec![0xff, 0xff, 0x42, 0x43],
+                vec![0xff, 0xff, 0xff, 0xff],
+            ]
+        );
+
+        // Surrounding pixels are unchanged
+        assert_eq!(buffer.get(1, 2).0, 0x21);
+        assert_eq!(buffer.get(6, 3).0, 0x36);
+        assert_eq!(buffer.get(3, 5).0, 0x53);
+
+        // Scrolling past the region size fills the whole region
+        buffer.scroll((0, 0), (3, 2), 0, 5, &PixelG8(0x01));
+        assert_eq!(buffer.get(2, 1).0, 0x01);
+        assert_eq!(buffer.get(3, 1).0, 0x13);
+    }
+
     const X: usize = 2;
     const Y: usize = 2;
 
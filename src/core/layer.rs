@@ -44,35 +44,20 @@ impl<'a, Pixel> Renderable<Pixel> for Layer<'a, Pixel> {
             return;
         }
 
-        // Update graphics context with new layer bounds
-        let gfx_bounds = graphics.get_bounds();
-        let mut new_bounds = gfx_bounds;
-        new_bounds.x += self.bounds.x;
-        new_bounds.y += self.bounds.y;
-        new_bounds.w = if gfx_bounds.w > self.bounds.w {
-            self.bounds.w
-        } else {
-            gfx_bounds.w
-        };
-        new_bounds.h = if gfx_bounds.h > self.bounds.h {
-            self.bounds.h
-        } else {
-            gfx_bounds.h
-        };
-        graphics.set_bounds(&new_bounds);
+        // Nest graphics context within the layer bounds, limited to the parent clip area
+        // and restored when the scope ends
+        let mut graphics = graphics.scope();
+        graphics.push_bounds(&self.bounds);
 
         // Render children
         for child in self.children.iter_mut() {
-            child.render(graphics, buffer);
+            child.render(&mut graphics, buffer);
         }
 
         // Render parent
         match self.renderer {
-            Some(ref mut r) => r.render(graphics, buffer),
+            Some(ref mut r) => r.render(&mut graphics, buffer),
             None => (),
         }
-
-        // Revert graphics context
-        graphics.set_bounds(&gfx_bounds);
     }
 }
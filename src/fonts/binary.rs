@@ -0,0 +1,355 @@
+//! Binary font format, allowing fonts to be loaded at runtime rather than compiled in.
+//!
+//! Binary fonts are parsed in place from a byte slice (for example from `include_bytes!`
+//! or memory mapped flash) without copying character data.
+//!
+//! All values are little endian. The format consists of:
+//!
+//! - a 12 byte header: magic (`UGFN`), version, size, height, ascent, and a u32 character count
+//! - a 12 byte entry per character, sorted by code point: a u24 code point, width, height,
+//!   advance, x and y bearings, and a u32 offset into the data section
+//! - the data section, containing packed character bitmaps
+//!
+//! Copyright 2019 Ryan Kurte
+
+use std::fmt;
+
+use super::{Char, Font, Glyphs};
+
+/// Binary font magic bytes
+pub const MAGIC: &[u8; 4] = b"UGFN";
+/// Current binary font format version
+pub const VERSION: u8 = 1;
+
+const HEADER_LEN: usize = 12;
+const ENTRY_LEN: usize = 12;
+
+/// Errors that can occur when parsing a binary font
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Error {
+    /// Data is too short for the header or character table
+    Truncated,
+    /// Magic bytes do not match
+    InvalidMagic,
+    /// Binary font version is not supported
+    UnsupportedVersion(u8),
+    /// Character code is not a valid unicode scalar value
+    InvalidCode(u32),
+    /// Character codes are not sorted or contain duplicates
+    Unsorted,
+    /// Character bitmap extends past the end of the data section
+    DataOutOfRange(char),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Truncated => write!(f, "binary font truncated"),
+            Error::InvalidMagic => write!(f, "invalid binary font magic"),
+            Error::UnsupportedVersion(v) => write!(f, "unsupported binary font version: {}", v),
+            Error::InvalidCode(c) => write!(f, "invalid character code: {:#x}", c),
+            Error::Unsorted => write!(f, "character codes not sorted"),
+            Error::DataOutOfRange(c) => write!(f, "character {:?} data out of range", c),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Binary font, referencing character data in the underlying buffer
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BinaryFont<'a> {
+    size: u8,
+    height: u8,
+    ascent: u8,
+    entries: &'a [u8],
+    data: &'a [u8],
+}
+
+impl<'a> BinaryFont<'a> {
+    /// Parse and validate a binary font
+    pub fn parse(buff: &'a [u8]) -> Result<Self, Error> {
+        if buff.len() < HEADER_LEN {
+            return Err(Error::Truncated);
+        }
+        if &buff[0..4] != MAGIC {
+            return Err(Error::InvalidMagic);
+        }
+        if buff[4] != VERSION {
+            return Err(Error::UnsupportedVersion(buff[4]));
+        }
+
+        let count = u32::from_le_bytes([buff[8], buff[9], buff[10], buff[11]]) as usize;
+        let table_end = count
+            .checked_mul(ENTRY_LEN)
+            .and_then(|l| l.checked_add(HEADER_LEN))
+            .ok_or(Error::Truncated)?;
+        if buff.len() < table_end {
+            return Err(Error::Truncated);
+        }
+
+        let f = BinaryFont {
+            size: buff[5],
+            height: buff[6],
+            ascent: buff[7],
+            entries: &buff[HEADER_LEN..table_end],
+            data: &buff[table_end..],
+        };
+
+        // Validate characters so lookups can not fail later
+        let mut last = None;
+        for i in 0..count {
+            let code = f.code(i);
+            let c = std::char::from_u32(code).ok_or(Error::InvalidCode(code))?;
+
+            match last {
+                Some(l) if l >= code => return Err(Error::Unsorted),
+                _ => last = Some(code),
+            }
+
+            let (offset, len) = f.data_range(i);
+            match offset.checked_add(len) {
+                Some(end) if end <= f.data.len() => (),
+                _ => return Err(Error::DataOutOfRange(c)),
+            }
+        }
+
+        Ok(f)
+    }
+
+    /// Nominal size the font was rendered at (in pixels per em)
+    pub fn size(&self) -> u8 {
+        self.size
+    }
+
+    /// Line height in pixels
+    pub fn height(&self) -> u8 {
+        self.height
+    }
+
+    /// Distance from the top of a line to the baseline in pixels
+    pub fn ascent(&self) -> u8 {
+        self.ascent
+    }
+
+    /// Number of characters in the font
+    pub fn len(&self) -> usize {
+        self.entries.len() / ENTRY_LEN
+    }
+
+    /// Check whether the font contains no characters
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Fetch the character for a given code point (if available)
+    pub fn get(&self, c: char) -> Option<Char<'a>> {
+        let (mut low, mut high) = (0, self.len());
+
+        while low < high {
+            let mid = (low + high) / 2;
+            let code = self.code(mid);
+
+            if code == c as u32 {
+                return Some(self.char(mid));
+            } else if code < c as u32 {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+
+        None
+    }
+
+    /// Iterate over the characters in the font
+    pub fn chars(&self) -> impl Iterator<Item = Char<'a>> + '_ {
+        (0..self.len()).map(move |i| self.char(i))
+    }
+
+    fn entry(&self, index: usize) -> &'a [u8] {
+        &self.entries[index * ENTRY_LEN..(index + 1) * ENTRY_LEN]
+    }
+
+    fn code(&self, index: usize) -> u32 {
+        let e = self.entry(index);
+        u32::from_le_bytes([e[0], e[1], e[2], 0])
+    }
+
+    fn data_range(&self, index: usize) -> (usize, usize) {
+        let e = self.entry(index);
+        let offset = u32::from_le_bytes([e[8], e[9], e[10], e[11]]) as usize;
+        (offset, Char::stride(e[3]) * e[4] as usize)
+    }
+
+    /// Build a character from a (validated) entry
+    fn char(&self, index: usize) -> Char<'a> {
+        let e = self.entry(index);
+        let (offset, len) = self.data_range(index);
+
+        Char {
+            code: std::char::from_u32(self.code(index)).unwrap(),
+            width: e[3],
+            height: e[4],
+            advance: e[5],
+            bearing_x: e[6] as i8,
+            bearing_y: e[7] as i8,
+            data: &self.data[offset..offset + len],
+        }
+    }
+}
+
+impl<'a> Glyphs for BinaryFont<'a> {
+    fn height(&self) -> u8 {
+        self.height
+    }
+
+    fn ascent(&self) -> u8 {
+        self.ascent
+    }
+
+    fn glyph(&self, c: char) -> Option<Char<'_>> {
+        self.get(c)
+    }
+}
+
+/// Encode a font into binary form
+///
+/// Characters must be sorted by code point, as with any `Font`.
+pub fn encode(font: &Font) -> Vec<u8> {
+    let mut b = Vec::with_capacity(HEADER_LEN + font.chars.len() * ENTRY_LEN);
+
+    b.extend_from_slice(MAGIC);
+    b.extend_from_slice(&[VERSION, font.size, font.height, font.ascent]);
+    b.extend_from_slice(&(font.chars.len() as u32).to_le_bytes());
+
+    let mut offset = 0;
+    for c in font.chars {
+        let len = Char::stride(c.width) * c.height as usize;
+
+        b.extend_from_slice(&(c.code as u32).to_le_bytes()[..3]);
+        b.extend_from_slice(&[
+            c.width,
+            c.height,
+            c.advance,
+            c.bearing_x as u8,
+            c.bearing_y as u8,
+        ]);
+        b.extend_from_slice(&(offset as u32).to_le_bytes());
+
+        offset += len;
+    }
+
+    for c in font.chars {
+        let len = Char::stride(c.width) * c.height as usize;
+        b.extend_from_slice(&c.data[..len]);
+    }
+
+    b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHARS: &[Char] = &[
+        Char {
+            code: 'a',
+            width: 2,
+            height: 2,
+            advance: 3,
+            bearing_x: -1,
+            bearing_y: 2,
+            data: &[0b1000_0000, 0b0100_0000],
+        },
+        Char {
+            code: ' ',
+            width: 0,
+            height: 0,
+            advance: 3,
+            bearing_x: 0,
+            bearing_y: 0,
+            data: &[],
+        },
+        Char {
+            code: '\u{2588}',
+            width: 10,
+            height: 1,
+            advance: 11,
+            bearing_x: 1,
+            bearing_y: -1,
+            data: &[0b1111_1111, 0b1100_0000],
+        },
+    ];
+
+    fn font() -> Font<'static> {
+        Font {
+            size: 2,
+            height: 3,
+            ascent: 2,
+            chars: CHARS,
+        }
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let mut chars = CHARS.to_vec();
+        chars.sort_by_key(|c| c.code);
+        let font = Font {
+            chars: &chars,
+            ..font()
+        };
+
+        let encoded = encode(&font);
+        let parsed = BinaryFont::parse(&encoded).unwrap();
+
+        assert_eq!(parsed.size(), 2);
+        assert_eq!(parsed.height(), 3);
+        assert_eq!(parsed.ascent(), 2);
+        assert_eq!(parsed.len(), 3);
+
+        for c in &chars {
+            assert_eq!(parsed.get(c.code), Some(*c));
+        }
+        assert_eq!(parsed.get('b'), None);
+        assert_eq!(parsed.chars().collect::<Vec<_>>(), chars);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let mut chars = CHARS.to_vec();
+        chars.sort_by_key(|c| c.code);
+        let encoded = encode(&Font {
+            chars: &chars,
+            ..font()
+        });
+
+        assert_eq!(BinaryFont::parse(&encoded[..8]), Err(Error::Truncated));
+        assert_eq!(BinaryFont::parse(&encoded[..20]), Err(Error::Truncated));
+
+        let mut e = encoded.clone();
+        e[0] = b'X';
+        assert_eq!(BinaryFont::parse(&e), Err(Error::InvalidMagic));
+
+        let mut e = encoded.clone();
+        e[4] = VERSION + 1;
+        assert_eq!(
+            BinaryFont::parse(&e),
+            Err(Error::UnsupportedVersion(VERSION + 1))
+        );
+
+        let mut e = encoded.clone();
+        e[HEADER_LEN + 2] = 0x11;
+        assert_eq!(BinaryFont::parse(&e), Err(Error::InvalidCode(0x110020)));
+
+        // Unsorted characters
+        let e = encode(&font());
+        assert_eq!(BinaryFont::parse(&e), Err(Error::Unsorted));
+
+        // Missing character data
+        assert_eq!(
+            BinaryFont::parse(&encoded[..encoded.len() - 1]),
+            Err(Error::DataOutOfRange('\u{2588}'))
+        );
+    }
+}
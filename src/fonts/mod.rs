@@ -0,0 +1,173 @@
+//! Fonts module defines the bitmap font representation used for text rendering.
+//! Fonts are generated from TTF/OTF or BDF/PCF sources using the `font-converter` utility,
+//! either as rust source or in a binary format that can be loaded at runtime.
+//! Stroke fonts provide scalable text from a single built-in font.
+//!
+//! Copyright 2019 Ryan Kurte
+
+pub mod binary;
+pub use self::binary::BinaryFont;
+pub mod stroke;
+pub use self::stroke::{StrokeChar, StrokeFont, SIMPLEX};
+
+/// Glyphs trait implemented by fonts that can be used for text rendering
+pub trait Glyphs {
+    /// Line height in pixels
+    fn height(&self) -> u8;
+    /// Distance from the top of a line to the baseline in pixels
+    fn ascent(&self) -> u8;
+    /// Fetch the character for a given code point (if available)
+    fn glyph(&self, c: char) -> Option<Char<'_>>;
+
+    /// Measure the width of a string in pixels (the widest line for multi-line strings)
+    fn text_width(&self, text: &str) -> usize {
+        text.split('\n')
+            .map(|l| {
+                l.chars()
+                    .filter_map(|c| self.glyph(c))
+                    .map(|c| c.advance as usize)
+                    .sum()
+            })
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Bitmap font containing a set of characters sorted by code point
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Font<'a> {
+    /// Nominal size the font was rendered at (in pixels per em)
+    pub size: u8,
+    /// Line height in pixels
+    pub height: u8,
+    /// Distance from the top of a line to the baseline in pixels
+    pub ascent: u8,
+    /// Characters, sorted by code point
+    pub chars: &'a [Char<'a>],
+}
+
+/// Bitmap character and associated metrics
+///
+/// Bitmap data is stored row by row, with each row padded to a whole number of bytes
+/// and the leftmost pixel in the most significant bit (matching `Buffer<PixelBW>`).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Char<'a> {
+    /// Character code point
+    pub code: char,
+    /// Bitmap width in pixels
+    pub width: u8,
+    /// Bitmap height in pixels
+    pub height: u8,
+    /// Horizontal distance to advance the pen after drawing the character
+    pub advance: u8,
+    /// Horizontal offset from the pen position to the left edge of the bitmap
+    pub bearing_x: i8,
+    /// Vertical offset from the baseline to the top edge of the bitmap (positive is up)
+    pub bearing_y: i8,
+    /// Packed bitmap data
+    pub data: &'a [u8],
+}
+
+impl<'a> Font<'a> {
+    /// Fetch the character for a given code point (if available)
+    pub fn get(&self, c: char) -> Option<&Char<'a>> {
+        self.chars
+            .binary_search_by_key(&c, |ch| ch.code)
+            .ok()
+            .map(|i| &self.chars[i])
+    }
+}
+
+impl<'a> Glyphs for Font<'a> {
+    fn height(&self) -> u8 {
+        self.height
+    }
+
+    fn ascent(&self) -> u8 {
+        self.ascent
+    }
+
+    fn glyph(&self, c: char) -> Option<Char<'_>> {
+        self.get(c).copied()
+    }
+}
+
+impl<'a> Char<'a> {
+    /// Compute the length of a bitmap row in bytes
+    pub fn stride(width: u8) -> usize {
+        (width as usize).div_ceil(8)
+    }
+
+    /// Fetch the state of a pixel in the character bitmap
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        if x >= self.width as usize || y >= self.height as usize {
+            return false;
+        }
+
+        let index = y * Self::stride(self.width) + x / 8;
+        let mask = 0x80 >> (x % 8);
+
+        self.data[index] & mask != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHARS: &[Char] = &[
+        Char {
+            code: 'a',
+            width: 2,
+            height: 2,
+            advance: 3,
+            bearing_x: 0,
+            bearing_y: 2,
+            data: &[0b1000_0000, 0b0100_0000],
+        },
+        Char {
+            code: 'c',
+            width: 10,
+            height: 1,
+            advance: 11,
+            bearing_x: 1,
+            bearing_y: 1,
+            data: &[0b0000_0000, 0b0100_0000],
+        },
+    ];
+
+    const FONT: Font = Font {
+        size: 2,
+        height: 3,
+        ascent: 2,
+        chars: CHARS,
+    };
+
+    #[test]
+    fn test_font_get() {
+        assert_eq!(FONT.get('a'), Some(&CHARS[0]));
+        assert_eq!(FONT.get('b'), None);
+        assert_eq!(FONT.get('c'), Some(&CHARS[1]));
+    }
+
+    #[test]
+    fn test_text_width() {
+        assert_eq!(FONT.text_width(""), 0);
+        assert_eq!(FONT.text_width("ac"), 14);
+        assert_eq!(FONT.text_width("a\nacb\nc"), 14);
+    }
+
+    #[test]
+    fn test_char_get() {
+        let a = &CHARS[0];
+        assert!(a.get(0, 0));
+        assert!(!a.get(1, 0));
+        assert!(!a.get(0, 1));
+        assert!(a.get(1, 1));
+        assert!(!a.get(2, 1));
+
+        let c = &CHARS[1];
+        assert!(!c.get(8, 0));
+        assert!(c.get(9, 0));
+    }
+}
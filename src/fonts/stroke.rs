@@ -0,0 +1,220 @@
+//! Stroke fonts define characters as line paths on a small grid (in the style of the Hershey
+//! fonts), so text can be drawn at any size from a single small font.
+//!
+//! Copyright 2019 Ryan Kurte
+
+/// Stroke font containing a set of characters sorted by code point
+///
+/// Character paths are defined in font units, with the top of capitals at zero and the
+/// baseline at `height`, and are scaled to the requested cap height when drawn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StrokeFont<'a> {
+    /// Cap height in font units
+    pub height: u8,
+    /// Distance between lines in font units
+    pub line_height: u8,
+    /// Horizontal space between characters in font units
+    pub spacing: u8,
+    /// Characters, sorted by code point
+    pub chars: &'a [StrokeChar<'a>],
+}
+
+/// Stroke character, with the path stored as space separated strokes
+///
+/// Each stroke is a sequence of points joined by lines, with each point stored as a pair of
+/// digits (x then y, in font units from the top left). Strokes with a single point are dots.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StrokeChar<'a> {
+    /// Character code point
+    pub code: char,
+    /// Width of the character path in font units
+    pub width: u8,
+    /// Character path
+    pub path: &'a str,
+}
+
+impl<'a> StrokeFont<'a> {
+    /// Fetch the character for a given code point, falling back to upper case for
+    /// characters missing from the font
+    pub fn get(&self, c: char) -> Option<&StrokeChar<'a>> {
+        let find = |c: char| {
+            self.chars
+                .binary_search_by_key(&c, |ch| ch.code)
+                .ok()
+                .map(|i| &self.chars[i])
+        };
+
+        find(c).or_else(|| find(c.to_ascii_uppercase()))
+    }
+
+    /// Scale a distance in font units to pixels for the provided cap height
+    pub fn scale(&self, units: isize, size: usize) -> isize {
+        let h = self.height as isize;
+        (units * size as isize + h / 2).div_euclid(h)
+    }
+
+    /// Horizontal advance for a character in pixels, for the provided cap height
+    pub fn advance(&self, ch: &StrokeChar, size: usize) -> isize {
+        self.scale((ch.width + self.spacing) as isize, size)
+    }
+
+    /// Measure the width of a string in pixels for the provided cap height
+    /// (the widest line for multi-line strings, including trailing character spacing)
+    pub fn text_width(&self, text: &str, size: usize) -> usize {
+        text.split('\n')
+            .map(|l| {
+                l.chars()
+                    .filter_map(|c| self.get(c))
+                    .map(|c| self.advance(c, size) as usize)
+                    .sum()
+            })
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl<'a> StrokeChar<'a> {
+    /// Iterate over the strokes in a character path, each as a sequence of points in font units
+    ///
+    /// Malformed points (with characters other than digits, or a trailing single digit)
+    /// are skipped.
+    pub fn strokes(&self) -> impl Iterator<Item = impl Iterator<Item = (u8, u8)> + 'a> + 'a {
+        self.path.split(' ').filter(|s| !s.is_empty()).map(|s| {
+            s.as_bytes()
+                .chunks_exact(2)
+                .filter(|p| p.iter().all(u8::is_ascii_digit))
+                .map(|p| (p[0] - b'0', p[1] - b'0'))
+        })
+    }
+}
+
+/// Build a stroke character (keeping the font table compact)
+const fn glyph(code: char, width: u8, path: &'static str) -> StrokeChar<'static> {
+    StrokeChar { code, width, path }
+}
+
+/// Simple single stroke font, covering digits, capitals (also used for lower case) and
+/// common punctuation, with an 8 unit cap height
+pub static SIMPLEX: StrokeFont<'static> = StrokeFont {
+    height: 8,
+    line_height: 12,
+    spacing: 2,
+    chars: &[
+        glyph(' ', 4, ""),
+        glyph('!', 0, "0006 08"),
+        glyph('"', 2, "0002 2022"),
+        glyph('#', 5, "1117 4147 0353 0555"),
+        glyph('%', 5, "5008 0010110100 4757584847"),
+        glyph('\'', 0, "0002"),
+        glyph('(', 2, "20020628"),
+        glyph(')', 2, "00222608"),
+        glyph('*', 4, "2125 0244 0442"),
+        glyph('+', 4, "2226 0444"),
+        glyph(',', 1, "171809"),
+        glyph('-', 4, "0444"),
+        glyph('.', 0, "08"),
+        glyph('/', 4, "4008"),
+        glyph('0', 5, "104051574818070110"),
+        glyph('1', 5, "123038 1858"),
+        glyph('2', 5, "01104051530858"),
+        glyph('3', 5, "01104051534424 445557481807"),
+        glyph('4', 5, "48400656"),
+        glyph('5', 5, "500004445557481807"),
+        glyph('6', 5, "4020020718485755441405"),
+        glyph('7', 5, "00505128"),
+        glyph('8', 5, "14030110405153441405071848575544"),
+        glyph('9', 5, "5344140301104051563818"),
+        glyph(':', 0, "03 08"),
+        glyph(';', 1, "13 171809"),
+        glyph('<', 4, "400448"),
+        glyph('=', 4, "0343 0545"),
+        glyph('>', 4, "004408"),
+        glyph('?', 5, "01104051532526 28"),
+        glyph('A', 5, "080220305258 0555"),
+        glyph('B', 5, "08004051534404 4455574808"),
+        glyph('C', 5, "5140100107184857"),
+        glyph('D', 5, "00305256380800"),
+        glyph('E', 5, "50000858 0444"),
+        glyph('F', 5, "500008 0444"),
+        glyph('G', 5, "51401001071848575434"),
+        glyph('H', 5, "0008 5058 0454"),
+        glyph('I', 2, "0020 1018 0828"),
+        glyph('J', 5, "505748180706"),
+        glyph('K', 5, "0008 5005 2358"),
+        glyph('L', 5, "000858"),
+        glyph('M', 6, "0800356068"),
+        glyph('N', 5, "08005850"),
+        glyph('O', 5, "104051574818070110"),
+        glyph('P', 5, "08004051534404"),
+        glyph('Q', 5, "104051574818070110 3658"),
+        glyph('R', 5, "08004051534404 2458"),
+        glyph('S', 5, "514010010314445557481807"),
+        glyph('T', 4, "0040 2028"),
+        glyph('U', 5, "000718485750"),
+        glyph('V', 4, "002840"),
+        glyph('W', 6, "0018335860"),
+        glyph('X', 5, "0058 5008"),
+        glyph('Y', 4, "002440 2428"),
+        glyph('Z', 5, "00500858"),
+        glyph('[', 2, "20000828"),
+        glyph(']', 2, "00202808"),
+        glyph('_', 5, "0959"),
+        glyph('|', 0, "0009"),
+        glyph('°', 2, "0020220200"),
+    ],
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simplex() {
+        // Characters are sorted and paths lie within the character bounds
+        for w in SIMPLEX.chars.windows(2) {
+            assert!(w[0].code < w[1].code, "{:?} out of order", w[1].code);
+        }
+        for ch in SIMPLEX.chars {
+            let pairs = |s: &str| s.as_bytes().chunks_exact(2).remainder().is_empty();
+            assert!(ch.path.split(' ').all(pairs), "{:?}", ch.code);
+            for (x, y) in ch.strokes().flatten() {
+                assert!(x <= ch.width && y <= 9, "{:?} point out of range", ch.code);
+            }
+        }
+    }
+
+    #[test]
+    fn test_stroke_font() {
+        assert_eq!(SIMPLEX.get('a'), SIMPLEX.get('A'));
+        assert_eq!(SIMPLEX.get('$'), None);
+
+        let strokes: Vec<Vec<_>> = SIMPLEX
+            .get('1')
+            .unwrap()
+            .strokes()
+            .map(|s| s.collect())
+            .collect();
+        assert_eq!(
+            strokes,
+            vec![vec![(1, 2), (3, 0), (3, 8)], vec![(1, 8), (5, 8)]]
+        );
+
+        // Advances are scaled from font units to the cap height
+        assert_eq!(SIMPLEX.text_width("10", 8), 14);
+        assert_eq!(SIMPLEX.text_width("1.\n0", 16), 18);
+    }
+
+    #[test]
+    fn test_malformed_path() {
+        let ch = StrokeChar {
+            code: 'x',
+            width: 4,
+            path: "00 4 1x2/22 334",
+        };
+        let strokes: Vec<Vec<_>> = ch.strokes().map(|s| s.collect()).collect();
+        assert_eq!(
+            strokes,
+            vec![vec![(0, 0)], vec![], vec![(2, 2)], vec![(3, 3)]]
+        );
+    }
+}
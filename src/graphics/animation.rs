@@ -0,0 +1,344 @@
+//! Sprite sheets and frame based animations
+//!
+//! Sprite sheets slice a single sprite into equally sized frames (in rows from the top left),
+//! which animations step through on tick events.
+//!
+//! Copyright 2019 Ryan Kurte
+
+use crate::core::buffer::Buff;
+use crate::core::window::OnEvent;
+use crate::types::events::Event;
+use crate::types::point::Point;
+
+use super::{Graphics, Renderable, Sprite};
+
+/// Sprite sheet containing equally sized frames, in rows from the top left
+pub struct SpriteSheet<'a, Pixel> {
+    sprite: &'a mut (dyn Sprite<Pixel> + 'a),
+    width: usize,
+    height: usize,
+    columns: usize,
+    count: usize,
+}
+
+impl<'a, Pixel> SpriteSheet<'a, Pixel> {
+    /// Create a sprite sheet of frames of the provided size, ignoring any partial frames
+    /// at the right and bottom edges of the sprite
+    pub fn new(sprite: &'a mut (dyn Sprite<Pixel> + 'a), width: usize, height: usize) -> Self {
+        assert!(width > 0 && height > 0, "invalid frame size");
+
+        let (w, h) = sprite.size();
+        let (columns, rows) = (w / width, h / height);
+        assert!(columns * rows > 0, "sprite smaller than frame size");
+
+        SpriteSheet {
+            sprite,
+            width,
+            height,
+            columns,
+            count: columns * rows,
+        }
+    }
+
+    /// Number of frames in the sheet
+    pub fn frames(&self) -> usize {
+        self.count
+    }
+
+    /// Fetch a frame from the sheet as a sprite
+    pub fn frame(&mut self, index: usize) -> Frame<'_, Pixel> {
+        assert!(index < self.count, "frame index out of range");
+
+        Frame {
+            sprite: self.sprite,
+            x: index % self.columns * self.width,
+            y: index / self.columns * self.height,
+            width: self.width,
+            height: self.height,
+        }
+    }
+}
+
+/// Single frame from a sprite sheet
+pub struct Frame<'b, Pixel> {
+    sprite: &'b mut dyn Sprite<Pixel>,
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+impl<'b, Pixel> Sprite<Pixel> for Frame<'b, Pixel> {
+    fn get(&mut self, x: usize, y: usize) -> &Pixel {
+        self.sprite.get(self.x + x, self.y + y)
+    }
+
+    fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+}
+
+/// Animation playback mode
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mode {
+    /// Restart from the first frame after the last frame
+    Loop,
+    /// Alternate between playing forwards and backwards
+    PingPong,
+    /// Play once and stop on the last frame
+    Once,
+}
+
+/// OnComplete trait called when a one-shot animation finishes
+pub trait OnComplete {
+    fn on_complete(&mut self);
+}
+
+/// Animation player, stepping through sprite sheet frames on tick events
+pub struct Animation<'a, Pixel> {
+    sheet: SpriteSheet<'a, Pixel>,
+    position: Point,
+    interval: usize,
+    mode: Mode,
+    frame: usize,
+    elapsed: usize,
+    reverse: bool,
+    complete: bool,
+    on_complete: Option<&'a mut (dyn OnComplete + 'a)>,
+}
+
+impl<'a, Pixel> Animation<'a, Pixel> {
+    /// Create an animation showing each frame for the provided interval (in milliseconds),
+    /// drawn with its top left corner at the provided position
+    pub fn new(
+        sheet: SpriteSheet<'a, Pixel>,
+        position: Point,
+        interval: usize,
+        mode: Mode,
+    ) -> Self {
+        assert!(interval > 0, "invalid frame interval");
+
+        Animation {
+            sheet,
+            position,
+            interval,
+            mode,
+            frame: 0,
+            elapsed: 0,
+            reverse: false,
+            complete: false,
+            on_complete: None,
+        }
+    }
+
+    /// Bind an optional handler called when a one-shot animation finishes
+    pub fn bind_on_complete(&mut self, on_complete: Option<&'a mut (dyn OnComplete + 'a)>) {
+        self.on_complete = on_complete;
+    }
+
+    /// Index of the current frame
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    /// Check whether a one-shot animation has finished
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Restart the animation from the first frame
+    pub fn reset(&mut self) {
+        self.frame = 0;
+        self.elapsed = 0;
+        self.reverse = false;
+        self.complete = false;
+    }
+
+    /// Advance the animation by the provided time (in milliseconds)
+    pub fn tick(&mut self, ms: usize) {
+        // Completed animations hold the last frame until reset
+        if self.complete {
+            return;
+        }
+
+        self.elapsed += ms;
+
+        while self.elapsed >= self.interval && !self.complete {
+            self.elapsed -= self.interval;
+            self.step();
+        }
+    }
+
+    /// Move to the next frame for the animation mode
+    fn step(&mut self) {
+        let last = self.sheet.frames() - 1;
+
+        match self.mode {
+            Mode::Loop => self.frame = if self.frame < last { self.frame + 1 } else { 0 },
+            Mode::PingPong if last > 0 => {
+                if (self.reverse && self.frame == 0) || (!self.reverse && self.frame == last) {
+                    self.reverse = !self.reverse;
+                }
+                self.frame = if self.reverse {
+                    self.frame - 1
+                } else {
+                    self.frame + 1
+                };
+            }
+            Mode::PingPong => (),
+            // One-shot animations finish once the last frame has been shown
+            Mode::Once if self.frame < last => self.frame += 1,
+            Mode::Once => {
+                self.complete = true;
+                self.elapsed = 0;
+                if let Some(ref mut h) = self.on_complete {
+                    h.on_complete();
+                }
+            }
+        }
+    }
+}
+
+impl<'a, Pixel> OnEvent for Animation<'a, Pixel> {
+    fn on_event(&mut self, e: &Event) {
+        if let Event::Tick { ms } = e {
+            self.tick(*ms);
+        }
+    }
+}
+
+impl<'a, Pixel> Renderable<Pixel> for Animation<'a, Pixel> {
+    fn render(&mut self, graphics: &mut Graphics<Pixel>, buffer: &mut dyn Buff<Pixel>) {
+        let mut frame = self.sheet.frame(self.frame);
+        graphics.draw_sprite(buffer, self.position, &mut frame);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::graphics::sprite::Bitmap;
+    use crate::graphics::test_utils::{bw_buffer, render, H, W};
+    use crate::types::pixel::PixelBW;
+
+    /// Two by two sheet of 2x2 frames, each with a single pixel set
+    const SHEET: [bool; 16] = [
+        true, false, false, true, //
+        false, false, false, false, //
+        false, false, false, false, //
+        true, false, false, true, //
+    ];
+
+    struct Counter(usize);
+
+    impl OnComplete for Counter {
+        fn on_complete(&mut self) {
+            self.0 += 1;
+        }
+    }
+
+    fn frames(animation: &mut Animation<PixelBW>, ticks: usize) -> Vec<usize> {
+        (0..ticks)
+            .map(|_| {
+                animation.on_event(&Event::Tick { ms: 10 });
+                animation.frame()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sprite_sheet() {
+        let mut bitmap = Bitmap::new(4, 4, &SHEET);
+        let mut sheet = SpriteSheet::new(&mut bitmap, 2, 2);
+        assert_eq!(sheet.frames(), 4);
+
+        let set: Vec<Vec<(usize, usize)>> = (0..4)
+            .map(|i| {
+                let mut f = sheet.frame(i);
+                assert_eq!(f.size(), (2, 2));
+                (0..4)
+                    .map(|p| (p % 2, p / 2))
+                    .filter(|&(x, y)| *f.get(x, y))
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            set,
+            vec![vec![(0, 0)], vec![(1, 0)], vec![(0, 1)], vec![(1, 1)]]
+        );
+
+        // Partial frames are ignored
+        let mut bitmap = Bitmap::new(4, 4, &SHEET);
+        assert_eq!(SpriteSheet::new(&mut bitmap, 3, 2).frames(), 2);
+    }
+
+    #[test]
+    fn test_animation_modes() {
+        let mut bitmap = Bitmap::new(4, 4, &SHEET);
+        let sheet = SpriteSheet::new(&mut bitmap, 2, 2);
+        let mut animation = Animation::new(sheet, Point::new(0, 0), 20, Mode::Loop);
+        assert_eq!(
+            frames(&mut animation, 10),
+            vec![0, 1, 1, 2, 2, 3, 3, 0, 0, 1]
+        );
+
+        let mut bitmap = Bitmap::new(4, 4, &SHEET);
+        let sheet = SpriteSheet::new(&mut bitmap, 2, 2);
+        let mut animation = Animation::new(sheet, Point::new(0, 0), 10, Mode::PingPong);
+        assert_eq!(frames(&mut animation, 8), vec![1, 2, 3, 2, 1, 0, 1, 2]);
+
+        let mut counter = Counter(0);
+        let mut bitmap = Bitmap::new(4, 4, &SHEET);
+        let sheet = SpriteSheet::new(&mut bitmap, 2, 2);
+        let mut animation = Animation::new(sheet, Point::new(0, 0), 10, Mode::Once);
+        animation.bind_on_complete(Some(&mut counter));
+
+        assert_eq!(frames(&mut animation, 3), vec![1, 2, 3]);
+        assert!(!animation.is_complete());
+        assert_eq!(frames(&mut animation, 3), vec![3, 3, 3]);
+        assert!(animation.is_complete());
+
+        // Time no longer accumulates once complete
+        animation.tick(usize::MAX);
+        animation.tick(usize::MAX);
+        assert_eq!(animation.frame(), 3);
+
+        animation.reset();
+        animation.tick(100);
+        assert_eq!(animation.frame(), 3);
+
+        drop(animation);
+        assert_eq!(counter.0, 2);
+    }
+
+    #[test]
+    fn test_draw_animation() {
+        let mut data = [0u8; W * H / 8];
+        let mut buffer = bw_buffer(&mut data);
+        let mut graphics = Graphics::new(0, 0, W, H);
+
+        let mut bitmap = Bitmap::new(4, 4, &SHEET);
+        let sheet = SpriteSheet::new(&mut bitmap, 2, 2);
+        let mut animation = Animation::new(sheet, Point::new(1, 1), 10, Mode::Loop);
+
+        for i in 0..4 {
+            graphics.push_bounds(&crate::types::rect::Rect::new(i * 3, 0, 3, 3));
+            animation.render(&mut graphics, &mut buffer);
+            graphics.pop();
+            animation.tick(10);
+        }
+
+        let expected = [
+            "................",
+            ".#...#..........",
+            ".......#...#....",
+            "................",
+            "................",
+            "................",
+            "................",
+            "................",
+        ];
+        assert_eq!(render(&buffer), expected);
+    }
+}
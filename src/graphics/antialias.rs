@@ -0,0 +1,277 @@
+//! Anti-aliased drawing functions, blending partial pixel coverage against the existing
+//! buffer contents. Colours that can't display partial coverage (such as `PixelBW`) fall
+//! back to the aliased equivalents.
+//!
+//! Copyright 2019 Ryan Kurte
+
+use crate::core::buffer::Buff;
+use crate::types::pixel::Blend;
+use crate::types::point::Point;
+
+use super::trig::{isqrt, Sector};
+use super::Graphics;
+
+/// Sub-pixel steps used when computing circle coverage
+const CIRCLE_STEPS: isize = 16;
+
+impl<Pixel: Blend> Graphics<Pixel> {
+    /// Blends a pixel over the existing buffer contents with the provided coverage
+    /// (0 for none, 255 for full)
+    pub fn blend(&self, b: &mut dyn Buff<Pixel>, x: isize, y: isize, p: &Pixel, alpha: u8) {
+        if let Some((x, y)) = self.buffer_pos(b, x, y) {
+            let under = b.get(x, y);
+            b.set(x, y, &under.blend(p, alpha));
+        }
+    }
+
+    /// Draws an anti-aliased line between two points using Xiaolin Wu's algorithm
+    pub fn draw_line_aa(&self, buf: &mut dyn Buff<Pixel>, a: Point, b: Point, p: &Pixel) {
+        if !Pixel::smooth() {
+            return self.line(buf, a, b, p);
+        }
+
+        if self.clip_line(a, b).is_none() {
+            return;
+        }
+
+        // Step along the major axis from the lower end, swapping axes for steep lines
+        let steep = (b.y - a.y).abs() > (b.x - a.x).abs();
+        let (a, b) = if steep {
+            (Point::new(a.y, a.x), Point::new(b.y, b.x))
+        } else {
+            (a, b)
+        };
+        let (a, b) = if a.x > b.x { (b, a) } else { (a, b) };
+        let (dx, dy) = (b.x - a.x, b.y - a.y);
+
+        let c = self.local_clip();
+        let (lo, hi) = if steep {
+            (c.y, c.bottom() - 1)
+        } else {
+            (c.x, c.right() - 1)
+        };
+
+        let mut plot = |x: isize, y: isize, alpha: u8| {
+            if steep {
+                self.blend(buf, y, x, p, alpha);
+            } else {
+                self.blend(buf, x, y, p, alpha);
+            }
+        };
+
+        for x in a.x.max(lo)..=b.x.min(hi) {
+            // Split the minor axis position into whole pixels and coverage of the next pixel
+            let (whole, frac) = match dx {
+                0 => (0, 0),
+                _ => {
+                    let t = (x - a.x) * dy;
+                    (t.div_euclid(dx), t.rem_euclid(dx) * 255 / dx)
+                }
+            };
+
+            plot(x, a.y + whole, 255 - frac as u8);
+            if frac > 0 {
+                plot(x, a.y + whole + 1, frac as u8);
+            }
+        }
+    }
+
+    /// Draws an anti-aliased circle outline, calling `visible` with each pixel offset
+    /// from the centre to select the pixels to be drawn
+    fn circle_aa<F>(
+        &self,
+        buf: &mut dyn Buff<Pixel>,
+        centre: Point,
+        radius: usize,
+        visible: F,
+        p: &Pixel,
+    ) where
+        F: Fn(isize, isize) -> bool,
+    {
+        let r2 = (radius * radius) as isize;
+
+        // Mirror offsets into each quadrant, skipping duplicates on the axes
+        let mut plot = |x: isize, y: isize, alpha: isize| {
+            for &(sx, sy) in &[(1, 1), (-1, 1), (-1, -1), (1, -1)] {
+                if (sx < 0 && x == 0) || (sy < 0 && y == 0) {
+                    continue;
+                }
+
+                let (x, y) = (x * sx, y * sy);
+                if visible(x, y) {
+                    self.blend(buf, centre.x + x, centre.y + y, p, alpha as u8);
+                }
+            }
+        };
+
+        // Columns up to the diagonal are stepped along x, the remainder along y
+        let diagonal = isqrt(r2 / 2);
+
+        for i in 0..=diagonal {
+            let pos = isqrt((r2 - i * i) * CIRCLE_STEPS * CIRCLE_STEPS);
+            let (whole, frac) = (pos / CIRCLE_STEPS, pos % CIRCLE_STEPS * 255 / CIRCLE_STEPS);
+
+            plot(i, whole, 255 - frac);
+            if frac > 0 {
+                plot(i, whole + 1, frac);
+            }
+
+            // Reflected pixels are skipped where they cross back over the diagonal
+            if whole != i {
+                plot(whole, i, 255 - frac);
+            }
+            if frac > 0 && whole + 1 != i {
+                plot(whole + 1, i, frac);
+            }
+        }
+    }
+
+    /// Draws an anti-aliased circle outline with the provided centre and radius
+    pub fn draw_circle_aa(
+        &self,
+        buf: &mut dyn Buff<Pixel>,
+        centre: Point,
+        radius: usize,
+        p: &Pixel,
+    ) {
+        if !Pixel::smooth() {
+            return self.draw_circle(buf, centre, radius, p);
+        }
+
+        self.circle_aa(buf, centre, radius, |_, _| true, p);
+    }
+
+    /// Draws an anti-aliased circular arc with the provided centre and radius, from the
+    /// start angle clockwise to the end angle (in degrees, with zero along the positive x axis)
+    pub fn draw_arc_aa(
+        &self,
+        buf: &mut dyn Buff<Pixel>,
+        centre: Point,
+        radius: usize,
+        start: isize,
+        end: isize,
+        p: &Pixel,
+    ) {
+        if !Pixel::smooth() {
+            return self.draw_arc(buf, centre, radius, start, end, p);
+        }
+
+        let sector = Sector::new(start, end);
+        self.circle_aa(buf, centre, radius, |x, y| sector.contains(x, y), p);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::core::buffer::Buffer;
+    use crate::graphics::stroke::Stroke;
+    use crate::graphics::test_utils::{bw_buffer, H, W};
+    use crate::types::pixel::PixelG8;
+
+    /// Fetch the rows of a grey-scale buffer
+    fn rows(buffer: &Buffer<PixelG8>) -> Vec<Vec<u8>> {
+        (0..H)
+            .map(|y| (0..W).map(|x| buffer.get(x, y).0).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_line_aa() {
+        let mut data = [0u8; W * H];
+        let mut buffer = Buffer::<PixelG8>::new(W, H, 0, 0, &mut data);
+        let graphics = Graphics::new(0, 0, W, H);
+
+        // Coverage is split between the two pixels nearest the line
+        graphics.draw_line_aa(
+            &mut buffer,
+            Point::new(0, 0),
+            Point::new(4, 1),
+            &PixelG8(0xff),
+        );
+        let r = rows(&buffer);
+        assert_eq!(&r[0][..6], &[255, 192, 128, 64, 0, 0]);
+        assert_eq!(&r[1][..6], &[0, 63, 127, 191, 255, 0]);
+
+        // Steep lines step along y, blending with the existing contents
+        graphics.draw_line_aa(
+            &mut buffer,
+            Point::new(9, 6),
+            Point::new(8, 2),
+            &PixelG8(0x80),
+        );
+        let r = rows(&buffer);
+        let column: Vec<_> = r.iter().map(|row| (row[8], row[9])).collect();
+        assert_eq!(
+            column,
+            vec![
+                (0, 0),
+                (0, 0),
+                (128, 0),
+                (96, 32),
+                (64, 64),
+                (32, 96),
+                (0, 128),
+                (0, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_circle_aa() {
+        let mut data = [0u8; W * H];
+        let mut buffer = Buffer::<PixelG8>::new(W, H, 0, 0, &mut data);
+        let graphics = Graphics::new(0, 0, W, H);
+
+        graphics.draw_circle_aa(&mut buffer, Point::new(4, 4), 3, &PixelG8(0xff));
+        let r = rows(&buffer);
+
+        // Pixels on the axes are fully covered, and the circle is symmetric
+        assert_eq!((r[1][4], r[7][4], r[4][1], r[4][7]), (255, 255, 255, 255));
+        for y in 1..8 {
+            for x in 1..8 {
+                assert_eq!(r[y][x], r[8 - y][x]);
+                assert_eq!(r[y][x], r[y][8 - x]);
+                assert_eq!(r[y][x], r[x][y]);
+            }
+        }
+
+        // Arcs only cover the selected sector
+        let mut data = [0u8; W * H];
+        let mut buffer = Buffer::<PixelG8>::new(W, H, 0, 0, &mut data);
+        graphics.draw_arc_aa(&mut buffer, Point::new(4, 4), 3, 0, 90, &PixelG8(0xff));
+        let r = rows(&buffer);
+        for y in 0..H {
+            for x in 0..W {
+                if r[y][x] != 0 {
+                    assert!(x >= 4 && y >= 4, "pixel ({}, {}) outside arc", x, y);
+                }
+            }
+        }
+        assert_eq!((r[4][7], r[7][4]), (255, 255));
+    }
+
+    #[test]
+    fn test_bw_fallback() {
+        let graphics = Graphics::new(0, 0, W, H);
+
+        let mut aliased = [0u8; W * H / 8];
+        let mut buffer = bw_buffer(&mut aliased);
+        graphics.draw_line(
+            &mut buffer,
+            Point::new(0, 7),
+            Point::new(15, 2),
+            &Stroke::default(),
+            &true,
+        );
+        graphics.draw_circle(&mut buffer, Point::new(8, 4), 3, &true);
+
+        let mut smooth = [0u8; W * H / 8];
+        let mut buffer = bw_buffer(&mut smooth);
+        graphics.draw_line_aa(&mut buffer, Point::new(0, 7), Point::new(15, 2), &true);
+        graphics.draw_circle_aa(&mut buffer, Point::new(8, 4), 3, &true);
+
+        assert_eq!(aliased, smooth);
+    }
+}
@@ -0,0 +1,231 @@
+//! Quadratic and cubic Bezier curves, flattened to polylines by adaptive subdivision
+//!
+//! Control points are subdivided in fixed point until each piece lies within a quarter
+//! of a pixel of its chord, so curves are drawn (and stroked and clipped) through the
+//! same paths as polylines.
+//!
+//! Copyright 2019 Ryan Kurte
+
+use crate::core::buffer::Buff;
+use crate::types::point::Point;
+
+use super::stroke::Stroke;
+use super::Graphics;
+
+/// Fractional bits of fixed point control points
+const SHIFT: u32 = 8;
+/// Maximum distance of a flattened segment from the curve, in fixed point
+const TOLERANCE: isize = (1 << SHIFT) / 4;
+/// Maximum subdivision depth, limiting a curve to 1024 segments
+const MAX_DEPTH: usize = 10;
+
+type Fixed = (isize, isize);
+
+fn fixed(p: Point) -> Fixed {
+    (p.x << SHIFT, p.y << SHIFT)
+}
+
+fn mid(a: Fixed, b: Fixed) -> Fixed {
+    ((a.0 + b.0) >> 1, (a.1 + b.1) >> 1)
+}
+
+/// Size of the second difference of three control points, which bounds the
+/// distance of the curve from its chord
+fn deviation(a: Fixed, b: Fixed, c: Fixed) -> isize {
+    let (dx, dy) = (a.0 - 2 * b.0 + c.0, a.1 - 2 * b.1 + c.1);
+    dx.abs().max(dy.abs())
+}
+
+/// Append a fixed point position to a path, rounded to the nearest pixel and skipping
+/// repeated points
+fn push(path: &mut Vec<Point>, p: Fixed) {
+    let half = 1 << (SHIFT - 1);
+    let p = Point::new((p.0 + half) >> SHIFT, (p.1 + half) >> SHIFT);
+
+    if path.last() != Some(&p) {
+        path.push(p);
+    }
+}
+
+fn quadratic_to(path: &mut Vec<Point>, c: [Fixed; 3], depth: usize) {
+    // The curve lies within a quarter of the second difference of the chord
+    if depth == 0 || deviation(c[0], c[1], c[2]) <= 4 * TOLERANCE {
+        return push(path, c[2]);
+    }
+
+    let (a, b) = (mid(c[0], c[1]), mid(c[1], c[2]));
+    let m = mid(a, b);
+
+    quadratic_to(path, [c[0], a, m], depth - 1);
+    quadratic_to(path, [m, b, c[2]], depth - 1);
+}
+
+fn cubic_to(path: &mut Vec<Point>, c: [Fixed; 4], depth: usize) {
+    // The curve lies within three quarters of the largest second difference of the chord
+    let d = deviation(c[0], c[1], c[2]).max(deviation(c[1], c[2], c[3]));
+    if depth == 0 || d * 3 <= 4 * TOLERANCE {
+        return push(path, c[3]);
+    }
+
+    let (a, b, e) = (mid(c[0], c[1]), mid(c[1], c[2]), mid(c[2], c[3]));
+    let (ab, be) = (mid(a, b), mid(b, e));
+    let m = mid(ab, be);
+
+    cubic_to(path, [c[0], a, ab, m], depth - 1);
+    cubic_to(path, [m, be, e, c[3]], depth - 1);
+}
+
+/// Flatten a quadratic Bezier curve to a polyline, from the start point towards
+/// the control point to the end point
+pub fn quadratic(start: Point, control: Point, end: Point) -> Vec<Point> {
+    let mut path = vec![start];
+    quadratic_to(
+        &mut path,
+        [fixed(start), fixed(control), fixed(end)],
+        MAX_DEPTH,
+    );
+    path
+}
+
+/// Flatten a cubic Bezier curve to a polyline, from the start point towards the
+/// two control points to the end point
+pub fn cubic(start: Point, c1: Point, c2: Point, end: Point) -> Vec<Point> {
+    let mut path = vec![start];
+    cubic_to(
+        &mut path,
+        [fixed(start), fixed(c1), fixed(c2), fixed(end)],
+        MAX_DEPTH,
+    );
+    path
+}
+
+impl<Pixel> Graphics<Pixel> {
+    /// Draws a quadratic Bezier curve from the start point to the end point with the
+    /// provided stroke style
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_quadratic(
+        &self,
+        buf: &mut dyn Buff<Pixel>,
+        start: Point,
+        control: Point,
+        end: Point,
+        stroke: &Stroke,
+        p: &Pixel,
+    ) {
+        self.draw_polyline(buf, &quadratic(start, control, end), stroke, p);
+    }
+
+    /// Draws a cubic Bezier curve from the start point to the end point with the provided
+    /// stroke style
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_cubic(
+        &self,
+        buf: &mut dyn Buff<Pixel>,
+        start: Point,
+        c1: Point,
+        c2: Point,
+        end: Point,
+        stroke: &Stroke,
+        p: &Pixel,
+    ) {
+        self.draw_polyline(buf, &cubic(start, c1, c2, end), stroke, p);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::graphics::test_utils::{bw_buffer, render, H, W};
+    use crate::types::rect::Rect;
+
+    #[test]
+    fn test_flatten() {
+        // Straight curves need no subdivision
+        let path = quadratic(Point::new(0, 0), Point::new(5, 5), Point::new(10, 10));
+        assert_eq!(path, vec![Point::new(0, 0), Point::new(10, 10)]);
+        let path = cubic(
+            Point::new(0, 0),
+            Point::new(3, 0),
+            Point::new(6, 0),
+            Point::new(9, 0),
+        );
+        assert_eq!(path, vec![Point::new(0, 0), Point::new(9, 0)]);
+
+        // Curves pass through their end points and the curve midpoint,
+        // with more segments for larger curves
+        let path = quadratic(Point::new(0, 0), Point::new(8, 16), Point::new(16, 0));
+        assert_eq!(path.first(), Some(&Point::new(0, 0)));
+        assert_eq!(path.last(), Some(&Point::new(16, 0)));
+        assert!(path.contains(&Point::new(8, 8)));
+
+        let large = quadratic(Point::new(0, 0), Point::new(80, 160), Point::new(160, 0));
+        assert!(large.len() > path.len());
+
+        let path = cubic(
+            Point::new(0, 0),
+            Point::new(0, 8),
+            Point::new(16, 8),
+            Point::new(16, 0),
+        );
+        assert_eq!(path.last(), Some(&Point::new(16, 0)));
+        assert!(path.contains(&Point::new(8, 6)));
+    }
+
+    #[test]
+    fn test_draw_bezier() {
+        let mut data = [0u8; W * H / 8];
+        let mut buffer = bw_buffer(&mut data);
+        let graphics = Graphics::new(0, 0, W, H);
+
+        graphics.draw_quadratic(
+            &mut buffer,
+            Point::new(0, 0),
+            Point::new(7, 14),
+            Point::new(14, 0),
+            &Stroke::default(),
+            &true,
+        );
+
+        let expected = [
+            "#.............#.",
+            ".#...........#..",
+            ".#...........#..",
+            "..#.........#...",
+            "...#.......#....",
+            "....#......#....",
+            "....#.....#.....",
+            ".....#####......",
+        ];
+        assert_eq!(render(&buffer), expected);
+
+        // Styled curves are stroked and clipped as polylines, with butt ends stopping at the
+        // end points
+        let mut data = [0u8; W * H / 8];
+        let mut buffer = bw_buffer(&mut data);
+        let mut graphics = graphics;
+        graphics.set_clip(&Rect::new(0, 0, 12, 8));
+
+        graphics.draw_cubic(
+            &mut buffer,
+            Point::new(1, 6),
+            Point::new(1, 0),
+            Point::new(14, 0),
+            Point::new(14, 6),
+            &Stroke::new(2),
+            &true,
+        );
+
+        let expected = [
+            "................",
+            "................",
+            "....#######.....",
+            "...#########....",
+            "..###...........",
+            "..##............",
+            ".##.............",
+            "................",
+        ];
+        assert_eq!(render(&buffer), expected);
+    }
+}
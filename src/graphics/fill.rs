@@ -0,0 +1,245 @@
+//! Fill sources for shape fills, providing solid colours, gradients and tiled patterns
+//!
+//! Gradients blend between colours on displays that support partial coverage, and are
+//! ordered dithered on those that don't (such as `PixelBW`).
+//!
+//! Copyright 2019 Ryan Kurte
+
+use crate::types::pixel::Blend;
+use crate::types::point::Point;
+use crate::types::rect::Rect;
+
+use super::trig::isqrt;
+
+/// Paint trait implemented by fill sources, providing the colour at each position
+pub trait Paint<Pixel> {
+    /// Call `f` with the colour at a position (relative to the graphics bounds),
+    /// or skip the position if it is transparent
+    fn paint<F: FnOnce(&Pixel)>(&self, x: isize, y: isize, f: F);
+}
+
+/// Pixels paint a single solid colour
+impl<Pixel> Paint<Pixel> for Pixel {
+    fn paint<F: FnOnce(&Pixel)>(&self, _x: isize, _y: isize, f: F) {
+        f(self)
+    }
+}
+
+/// 8x8 tiled bitmap pattern, with one byte per row and the leftmost pixel in the MSB
+pub type Pattern = [u8; 8];
+
+/// Alternating pixels (50% grey)
+pub const CHECKER: Pattern = [0xaa, 0x55, 0xaa, 0x55, 0xaa, 0x55, 0xaa, 0x55];
+/// Sparse dots (25% grey)
+pub const DOTS: Pattern = [0x88, 0x00, 0x22, 0x00, 0x88, 0x00, 0x22, 0x00];
+/// Horizontal lines
+pub const HORIZONTAL: Pattern = [0xff, 0x00, 0x00, 0x00, 0xff, 0x00, 0x00, 0x00];
+/// Vertical lines
+pub const VERTICAL: Pattern = [0x88; 8];
+/// Diagonal lines, rising to the right
+pub const DIAGONAL: Pattern = [0x11, 0x22, 0x44, 0x88, 0x11, 0x22, 0x44, 0x88];
+/// Crossed diagonal lines
+pub const CROSSHATCH: Pattern = [0x11, 0xaa, 0x44, 0xaa, 0x11, 0xaa, 0x44, 0xaa];
+
+/// Ordered dither thresholds for displays without partial coverage
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Fill source for shape fills
+#[derive(Clone, Debug, PartialEq)]
+pub enum Fill<Pixel> {
+    /// Single solid colour
+    Solid(Pixel),
+    /// Linear gradient between two colours, from the start point to the end point
+    /// and extended beyond them
+    Linear {
+        start: Point,
+        end: Point,
+        from: Pixel,
+        to: Pixel,
+    },
+    /// Radial gradient from a colour at the centre to a colour at the radius and beyond
+    Radial {
+        centre: Point,
+        radius: usize,
+        from: Pixel,
+        to: Pixel,
+    },
+    /// Tiled pattern, with set bits in the foreground colour and clear bits in the
+    /// background colour (or left unchanged if there is no background)
+    Pattern {
+        pattern: Pattern,
+        fg: Pixel,
+        bg: Option<Pixel>,
+    },
+}
+
+impl<Pixel> Fill<Pixel> {
+    /// Create a linear gradient between two points
+    pub fn linear(start: Point, end: Point, from: Pixel, to: Pixel) -> Self {
+        Fill::Linear {
+            start,
+            end,
+            from,
+            to,
+        }
+    }
+
+    /// Create a horizontal gradient across a rectangle, from left to right
+    pub fn horizontal(r: Rect, from: Pixel, to: Pixel) -> Self {
+        let end = Point::new(r.right() - 1, r.y);
+        Self::linear(r.origin(), end, from, to)
+    }
+
+    /// Create a vertical gradient across a rectangle, from top to bottom
+    pub fn vertical(r: Rect, from: Pixel, to: Pixel) -> Self {
+        let end = Point::new(r.x, r.bottom() - 1);
+        Self::linear(r.origin(), end, from, to)
+    }
+
+    /// Create a radial gradient around a centre point
+    pub fn radial(centre: Point, radius: usize, from: Pixel, to: Pixel) -> Self {
+        Fill::Radial {
+            centre,
+            radius,
+            from,
+            to,
+        }
+    }
+
+    /// Create a tiled pattern fill, with an optional background colour
+    pub fn pattern(pattern: Pattern, fg: Pixel, bg: Option<Pixel>) -> Self {
+        Fill::Pattern { pattern, fg, bg }
+    }
+}
+
+/// Colour at a position along a gradient (0 to 255 from start to end)
+fn gradient<Pixel: Blend + Clone>(from: &Pixel, to: &Pixel, t: isize, x: isize, y: isize) -> Pixel {
+    let t = t.clamp(0, 255);
+
+    if Pixel::smooth() {
+        return from.blend(to, t as u8);
+    }
+
+    let threshold = BAYER[y.rem_euclid(4) as usize][x.rem_euclid(4) as usize] as isize * 16 + 8;
+    if t > threshold {
+        to.clone()
+    } else {
+        from.clone()
+    }
+}
+
+impl<Pixel: Blend + Clone> Paint<Pixel> for Fill<Pixel> {
+    fn paint<F: FnOnce(&Pixel)>(&self, x: isize, y: isize, f: F) {
+        match self {
+            Fill::Solid(p) => f(p),
+            Fill::Linear {
+                start,
+                end,
+                from,
+                to,
+            } => {
+                // Project the position onto the gradient axis
+                let (dx, dy) = (end.x - start.x, end.y - start.y);
+                let len2 = dx * dx + dy * dy;
+                let t = match len2 {
+                    0 => 255,
+                    _ => (((x - start.x) * dx + (y - start.y) * dy) * 510 + len2)
+                        .div_euclid(2 * len2),
+                };
+
+                f(&gradient(from, to, t, x, y))
+            }
+            Fill::Radial {
+                centre,
+                radius,
+                from,
+                to,
+            } => {
+                // Distance from the centre in sixteenths of a pixel
+                let (dx, dy) = (x - centre.x, y - centre.y);
+                let dist = isqrt((dx * dx + dy * dy) * 256);
+                let t = match *radius {
+                    0 => 255,
+                    r => (dist * 510 + r as isize * 16) / (r as isize * 32),
+                };
+
+                f(&gradient(from, to, t, x, y))
+            }
+            Fill::Pattern { pattern, fg, bg } => {
+                let row = pattern[y.rem_euclid(8) as usize];
+                if row & (0x80 >> x.rem_euclid(8)) != 0 {
+                    f(fg)
+                } else if let Some(bg) = bg {
+                    f(bg)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::types::pixel::PixelG8;
+
+    fn colour<Pixel: Blend + Clone>(fill: &Fill<Pixel>, x: isize, y: isize) -> Option<Pixel> {
+        let mut c = None;
+        Paint::<Pixel>::paint(fill, x, y, |p| c = Some(p.clone()));
+        c
+    }
+
+    #[test]
+    fn test_linear() {
+        let fill = Fill::horizontal(Rect::new(2, 0, 5, 3), PixelG8(0), PixelG8(200));
+        let row: Vec<_> = (0..8).map(|x| colour(&fill, x, 1).unwrap().0).collect();
+        assert_eq!(row, vec![0, 0, 0, 50, 100, 150, 200, 200]);
+
+        // Angled gradients are constant perpendicular to the axis
+        let fill = Fill::linear(Point::new(0, 0), Point::new(4, 4), PixelG8(0), PixelG8(255));
+        assert_eq!(colour(&fill, 4, 0), colour(&fill, 0, 4));
+        assert_eq!(colour(&fill, 4, 0), colour(&fill, 2, 2));
+        assert_eq!(colour(&fill, 4, 4), Some(PixelG8(255)));
+    }
+
+    #[test]
+    fn test_radial() {
+        let fill = Fill::radial(Point::new(4, 4), 4, PixelG8(255), PixelG8(0));
+        assert_eq!(colour(&fill, 4, 4), Some(PixelG8(255)));
+        assert_eq!(colour(&fill, 6, 4), colour(&fill, 4, 2));
+        assert_eq!(colour(&fill, 6, 4), Some(PixelG8(127)));
+        assert_eq!(colour(&fill, 9, 9), Some(PixelG8(0)));
+    }
+
+    #[test]
+    fn test_pattern() {
+        let fill = Fill::pattern(CHECKER, PixelG8(1), None);
+        assert_eq!(colour(&fill, 0, 0), Some(PixelG8(1)));
+        assert_eq!(colour(&fill, 1, 0), None);
+        assert_eq!(colour(&fill, -1, 1), Some(PixelG8(1)));
+        assert_eq!(colour(&fill, 9, 9), Some(PixelG8(1)));
+        assert_eq!(colour(&fill, 9, 8), None);
+
+        let fill = Fill::pattern(VERTICAL, true, Some(false));
+        let row: Vec<_> = (0..8).map(|x| colour(&fill, x, 3).unwrap()).collect();
+        assert_eq!(
+            row,
+            vec![true, false, false, false, true, false, false, false]
+        );
+    }
+
+    #[test]
+    fn test_dithered_gradient() {
+        // Coverage of a dithered gradient on a black and white display follows the gradient
+        let fill = Fill::horizontal(Rect::new(0, 0, 16, 4), false, true);
+        let counts: Vec<usize> = (0..16)
+            .step_by(4)
+            .map(|x0| {
+                let cells = (x0..x0 + 4).flat_map(|x| (0..4).map(move |y| (x, y)));
+                cells.filter(|&(x, y)| colour(&fill, x, y).unwrap()).count()
+            })
+            .collect();
+
+        assert_eq!(counts, vec![1, 7, 11, 15]);
+    }
+}
@@ -0,0 +1,283 @@
+//! Flood and boundary fills, replacing contiguous areas of a buffer from a seed point
+//!
+//! Fills use a scanline span algorithm with a fixed size stack of spans rather than
+//! recursion or heap allocation, so memory use is bounded regardless of the filled outline.
+//! Spans dropped when the stack overflows are recovered by rescanning the area they came
+//! from for unfilled pixels bordering the fill, repeating until a pass finds none.
+//!
+//! Copyright 2019 Ryan Kurte
+
+use crate::core::buffer::Buff;
+use crate::types::point::Point;
+
+use super::Graphics;
+
+/// Maximum number of pending spans held while filling
+pub const FILL_STACK: usize = 32;
+
+/// Span of x coordinates (inclusive) to be scanned on a row, with the direction of the
+/// row it was found from
+type Span = (isize, isize, isize, isize);
+
+/// Fixed capacity span stack, recording the bounds (x1, x2, y1, y2) of spans dropped when full
+struct SpanStack {
+    spans: [Span; FILL_STACK],
+    len: usize,
+    dropped: Option<(isize, isize, isize, isize)>,
+}
+
+impl SpanStack {
+    fn new() -> Self {
+        SpanStack {
+            spans: [(0, 0, 0, 0); FILL_STACK],
+            len: 0,
+            dropped: None,
+        }
+    }
+
+    fn push(&mut self, span: Span) {
+        if self.len < FILL_STACK {
+            self.spans[self.len] = span;
+            self.len += 1;
+        } else {
+            let (x1, x2, y, _) = span;
+            self.dropped = Some(match self.dropped {
+                Some((a1, a2, b1, b2)) => (a1.min(x1), a2.max(x2), b1.min(y), b2.max(y)),
+                None => (x1, x2, y, y),
+            });
+        }
+    }
+
+    fn pop(&mut self) -> Option<Span> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        Some(self.spans[self.len])
+    }
+}
+
+impl<Pixel: PartialEq> Graphics<Pixel> {
+    /// Fills the contiguous area of the seed point's colour with the provided colour,
+    /// limited to the clip area
+    pub fn flood_fill(&self, buf: &mut dyn Buff<Pixel>, seed: Point, p: &Pixel) {
+        let target = match self.buffer_pos(buf, seed.x, seed.y) {
+            Some((x, y)) => buf.get(x, y),
+            None => return,
+        };
+
+        // Already filled, and the fill would never terminate
+        if target == *p {
+            return;
+        }
+
+        self.span_fill(buf, seed, |c| *c == target, p)
+    }
+
+    /// Fills the area around the seed point up to a boundary colour with the provided colour,
+    /// limited to the clip area
+    pub fn boundary_fill(
+        &self,
+        buf: &mut dyn Buff<Pixel>,
+        seed: Point,
+        boundary: &Pixel,
+        p: &Pixel,
+    ) {
+        self.span_fill(buf, seed, |c| c != boundary && c != p, p)
+    }
+
+    /// Scanline span fill over pixels matching `inside`, which must no longer match once set
+    ///
+    /// Pixels already set to the fill colour next to the rescanned area of an overflowing
+    /// fill are treated as part of it.
+    fn span_fill<F>(&self, buf: &mut dyn Buff<Pixel>, seed: Point, inside: F, p: &Pixel)
+    where
+        F: Fn(&Pixel) -> bool,
+    {
+        let test = |buf: &dyn Buff<Pixel>, x: isize, y: isize| match self.buffer_pos(buf, x, y) {
+            Some((x, y)) => inside(&buf.get(x, y)),
+            None => false,
+        };
+        let filled = |buf: &dyn Buff<Pixel>, x: isize, y: isize| match self.buffer_pos(buf, x, y) {
+            Some((x, y)) => buf.get(x, y) == *p,
+            None => false,
+        };
+
+        if !test(buf, seed.x, seed.y) {
+            return;
+        }
+
+        let mut spans = SpanStack::new();
+        self.fill_spans(buf, &mut spans, seed, &test, p);
+
+        // Each dropped span borders a filled row, so any pixels it would have filled
+        // are found next to filled pixels within the bounds of the dropped spans
+        while let Some((x1, x2, y1, y2)) = spans.dropped.take() {
+            for y in y1..=y2 {
+                for x in x1..=x2 {
+                    if test(buf, x, y) && (filled(buf, x, y - 1) || filled(buf, x, y + 1)) {
+                        self.fill_spans(buf, &mut spans, Point::new(x, y), &test, p);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Fills spans outwards from a seed point until the stack is empty
+    fn fill_spans<F>(
+        &self,
+        buf: &mut dyn Buff<Pixel>,
+        spans: &mut SpanStack,
+        seed: Point,
+        test: &F,
+        p: &Pixel,
+    ) where
+        F: Fn(&dyn Buff<Pixel>, isize, isize) -> bool,
+    {
+        spans.push((seed.x, seed.x, seed.y, 1));
+        spans.push((seed.x, seed.x, seed.y - 1, -1));
+
+        while let Some((mut x1, x2, y, dy)) = spans.pop() {
+            let mut x = x1;
+
+            // Extend the span to the left, scanning back up past the start of the parent span
+            if test(buf, x, y) {
+                while test(buf, x - 1, y) {
+                    self.set(buf, x - 1, y, p);
+                    x -= 1;
+                }
+                if x < x1 {
+                    spans.push((x, x1 - 1, y - dy, -dy));
+                }
+            }
+
+            // Fill runs along the span, scanning onwards from each and back past the end
+            // of the parent span
+            while x1 <= x2 {
+                while test(buf, x1, y) {
+                    self.set(buf, x1, y, p);
+                    x1 += 1;
+                }
+                if x1 > x {
+                    spans.push((x, x1 - 1, y + dy, dy));
+                }
+                if x1 - 1 > x2 {
+                    spans.push((x2 + 1, x1 - 1, y - dy, -dy));
+                }
+
+                x1 += 1;
+                while x1 < x2 && !test(buf, x1, y) {
+                    x1 += 1;
+                }
+                x = x1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::core::buffer::Buffer;
+    use crate::graphics::stroke::Stroke;
+    use crate::graphics::test_utils::{bw_buffer, render, H, W};
+    use crate::types::pixel::PixelBW;
+    use crate::types::rect::Rect;
+
+    #[test]
+    fn test_flood_fill() {
+        let mut data = [0u8; W * H / 8];
+        let mut buffer = bw_buffer(&mut data);
+        let graphics = Graphics::new(0, 0, W, H);
+
+        // Concave outline, split in two by a small rectangle
+        let outline = [
+            Point::new(0, 0),
+            Point::new(4, 0),
+            Point::new(4, 4),
+            Point::new(7, 4),
+            Point::new(7, 0),
+            Point::new(11, 0),
+            Point::new(11, 7),
+            Point::new(0, 7),
+            Point::new(0, 0),
+        ];
+        graphics.draw_polyline(&mut buffer, &outline, &Stroke::default(), &true);
+        graphics.draw_rect(
+            &mut buffer,
+            Rect::new(7, 5, 2, 1),
+            &Stroke::default(),
+            &true,
+        );
+
+        graphics.boundary_fill(&mut buffer, Point::new(2, 2), &true, &true);
+
+        let expected = [
+            "#####..#####....",
+            "#####..#...#....",
+            "#####..#...#....",
+            "#####..#...#....",
+            "########...#....",
+            "##########.#....",
+            "##########.#....",
+            "############....",
+        ];
+        assert_eq!(render(&buffer), expected);
+
+        // Flood fills replace the seed colour, and are limited to the clip area
+        let mut graphics = graphics;
+        graphics.set_clip(&Rect::new(0, 0, 10, 8));
+        graphics.flood_fill(&mut buffer, Point::new(8, 2), &true);
+        graphics.flood_fill(&mut buffer, Point::new(2, 2), &false);
+
+        let expected = [
+            "..........##....",
+            "...........#....",
+            "...........#....",
+            "...........#....",
+            "...........#....",
+            "...........#....",
+            "...........#....",
+            "..........##....",
+        ];
+        assert_eq!(render(&buffer), expected);
+    }
+
+    /// Draw a comb outline with teeth hanging from the top edge every second column
+    fn comb(graphics: &Graphics<PixelBW>, buffer: &mut dyn Buff<PixelBW>, width: usize) {
+        graphics.draw_rect(buffer, Rect::new(0, 0, width, H), &Stroke::default(), &true);
+        for x in (2..width as isize - 2).step_by(2) {
+            graphics.draw_line(
+                buffer,
+                Point::new(x, 1),
+                Point::new(x, 5),
+                &Stroke::default(),
+                &true,
+            );
+        }
+    }
+
+    #[test]
+    fn test_fill_stack() {
+        let mut data = [0u8; W * H / 8];
+        let mut buffer = bw_buffer(&mut data);
+        let graphics = Graphics::new(0, 0, W, H);
+
+        comb(&graphics, &mut buffer, W);
+        graphics.boundary_fill(&mut buffer, Point::new(1, 6), &true, &true);
+        assert_eq!(render(&buffer), vec!["#".repeat(W); H]);
+
+        // Gaps between teeth each need a span, overflowing the stack on wider combs
+        const WIDE: usize = FILL_STACK * 4;
+        let mut data = [0u8; WIDE * H / 8];
+        let mut buffer = Buffer::<PixelBW>::new(WIDE, H, 0, 0, &mut data);
+        let graphics = Graphics::new(0, 0, WIDE, H);
+
+        comb(&graphics, &mut buffer, WIDE);
+        graphics.boundary_fill(&mut buffer, Point::new(1, 6), &true, &true);
+
+        // Dropped spans are recovered by rescanning, completing the fill
+        assert!((0..WIDE).all(|x| (0..H).all(|y| buffer.get(x, y))));
+    }
+}
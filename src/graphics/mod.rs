@@ -4,230 +4,1810 @@
 //! Copyright 2019 Ryan Kurte
 
 use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
 
 use crate::core::buffer::Buff;
+use crate::fonts::{Glyphs, StrokeFont};
 use crate::types::point::Point;
 use crate::types::rect::Rect;
 
+pub mod animation;
+pub mod antialias;
+pub mod bezier;
+pub mod fill;
+pub mod flood;
+pub mod rle;
+use self::fill::Paint;
+pub mod sprite;
+pub use self::sprite::Sprite;
+pub mod stroke;
+use self::stroke::{Cap, Dasher, Stroke};
+#[cfg(test)]
+pub(crate) mod test_utils;
+pub mod trig;
+use self::trig::{isqrt, Sector};
+
 /// Renderable trait implemented by types that can render themselves
 /// For example, widgets should implement the renderable trait to be bound into layers
 pub trait Renderable<Pixel> {
     fn render(&mut self, graphics: &mut Graphics<Pixel>, buffer: &mut Buff<Pixel>);
 }
 
-/// Sprite trait implemented by types that can be rendered from a buffer
-pub trait Sprite<Pixel> {
-    fn get(&mut self, x: usize, y: usize) -> &Pixel;
-    fn size(&self) -> (usize, usize);
+// Cohen-Sutherland outcodes for points outside the graphics bounds
+const LEFT: u8 = 1;
+const RIGHT: u8 = 2;
+const TOP: u8 = 4;
+const BOTTOM: u8 = 8;
+
+/// Rule used to determine the inside of self-intersecting polygons
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FillRule {
+    /// Points are inside when a ray from them crosses an odd number of edges
+    EvenOdd,
+    /// Points are inside when the polygon winds around them a non-zero number of times
+    NonZero,
 }
 
 /// Graphics context used for rendering components
 /// This includes offsets and dimensions to shift rendering scopes
 pub struct Graphics<Pixel> {
-    x: usize,
-    y: usize,
-    w: usize,
-    h: usize,
+    /// Bounds of the current rendering scope, in buffer coordinates
+    bounds: Rect,
+    /// Visible area of the current rendering scope, in buffer coordinates
+    clip: Rect,
+    /// Saved bounds and clip areas, restored by `pop`
+    stack: Vec<(Rect, Rect)>,
     _pixel: PhantomData<Pixel>,
 }
 
+/// Scope guard over a graphics context, restoring the bounds and clip area when dropped
+/// (including on early returns and panics)
+pub struct Scope<'a, Pixel> {
+    graphics: &'a mut Graphics<Pixel>,
+    depth: usize,
+}
+
+impl<'a, Pixel> Deref for Scope<'a, Pixel> {
+    type Target = Graphics<Pixel>;
+
+    fn deref(&self) -> &Graphics<Pixel> {
+        self.graphics
+    }
+}
+
+impl<'a, Pixel> DerefMut for Scope<'a, Pixel> {
+    fn deref_mut(&mut self) -> &mut Graphics<Pixel> {
+        self.graphics
+    }
+}
+
+impl<'a, Pixel> Drop for Scope<'a, Pixel> {
+    fn drop(&mut self) {
+        while self.graphics.stack.len() > self.depth {
+            self.graphics.pop();
+        }
+    }
+}
+
 impl<Pixel> Graphics<Pixel> {
     /// New creates a new graphcs context with the provided offsets and limits
-    pub fn new(x: usize, y: usize, w: usize, h: usize) -> Self {
+    pub fn new(x: isize, y: isize, w: usize, h: usize) -> Self {
+        let bounds = Rect::new(x, y, w, h);
+
         return Self {
-            x,
-            y,
-            w,
-            h,
+            bounds,
+            clip: bounds,
+            stack: Vec::new(),
             _pixel: PhantomData,
         };
     }
 
     /// Set wraps a buffer in a graphics context to shift rendering functions
-    pub fn set(&self, b: &mut Buff<Pixel>, x: usize, y: usize, p: &Pixel) {
-        let new_x = self.x + x;
-        let new_y = self.y + y;
+    /// Pixels outside the clip area or the underlying buffer are skipped.
+    pub fn set(&self, b: &mut dyn Buff<Pixel>, x: isize, y: isize, p: &Pixel) {
+        if let Some((x, y)) = self.buffer_pos(b, x, y) {
+            b.set(x, y, p);
+        }
+    }
 
-        if x < self.w && y < self.h {
-            b.set(new_x, new_y, p);
+    /// Sets a pixel using the colour of a fill source at that position
+    fn plot<F: Paint<Pixel>>(&self, b: &mut dyn Buff<Pixel>, x: isize, y: isize, p: &F) {
+        p.paint(x, y, |p| self.set(b, x, y, p));
+    }
+
+    /// Converts a position to buffer coordinates, returning None if it is outside the
+    /// clip area or the underlying buffer
+    fn buffer_pos(&self, b: &dyn Buff<Pixel>, x: isize, y: isize) -> Option<(usize, usize)> {
+        let pos = self.bounds.origin() + Point::new(x, y);
+        if !self.clip.contains(pos) {
+            return None;
+        }
+
+        let (w, h) = b.size();
+
+        if pos.x >= 0 && pos.y >= 0 && (pos.x as usize) < w && (pos.y as usize) < h {
+            Some((pos.x as usize, pos.y as usize))
+        } else {
+            None
         }
     }
 
     pub fn get_bounds(&mut self) -> Rect {
-        return Rect::new(self.x, self.y, self.w, self.h);
+        return self.bounds;
     }
 
     /// Set new bounds for rendering
-    /// This will offset and limit rendering by the provided values.
+    /// This will offset and limit rendering by the provided values, replacing any clip area.
     pub fn set_bounds(&mut self, bounds: &Rect) {
-        self.x = bounds.x;
-        self.y = bounds.y;
-        self.w = bounds.w;
-        self.h = bounds.h;
+        self.bounds = *bounds;
+        self.clip = *bounds;
+    }
+
+    /// Fetch the clip area, in buffer coordinates
+    pub fn get_clip(&self) -> Rect {
+        self.clip
     }
 
-    fn limit(min: usize, max: usize, actual: usize) -> usize {
-        if actual > max {
-            return max;
+    /// Set the clip area (in buffer coordinates), limited to the current bounds
+    pub fn set_clip(&mut self, clip: &Rect) {
+        self.clip = self.bounds.intersect(clip).unwrap_or_default();
+    }
+
+    /// Set nested bounds for rendering, with the provided rectangle relative to the
+    /// current bounds and the visible area limited to the current clip area
+    pub fn nest_bounds(&mut self, bounds: &Rect) {
+        let clip = self.clip;
+
+        self.set_bounds(&bounds.translate(self.bounds.origin()));
+        self.set_clip(&clip);
+    }
+
+    /// Save the current bounds and clip area, to be restored by `pop`
+    pub fn push(&mut self) {
+        self.stack.push((self.bounds, self.clip));
+    }
+
+    /// Restore the bounds and clip area saved by the last push
+    pub fn pop(&mut self) {
+        if let Some((bounds, clip)) = self.stack.pop() {
+            self.bounds = bounds;
+            self.clip = clip;
         }
-        if actual < min {
-            return min;
+    }
+
+    /// Save the current state and limit the clip area to the provided rectangle (relative
+    /// to the current bounds), within the existing clip area
+    pub fn push_clip(&mut self, clip: &Rect) {
+        self.push();
+        self.clip = self
+            .clip
+            .intersect(&clip.translate(self.bounds.origin()))
+            .unwrap_or_default();
+    }
+
+    /// Save the current state and move the origin by the provided offset, keeping the
+    /// existing clip area
+    pub fn push_translation(&mut self, offset: Point) {
+        self.push();
+        self.bounds = self.bounds.translate(offset);
+    }
+
+    /// Save the current state and nest the bounds (see `nest_bounds`)
+    pub fn push_bounds(&mut self, bounds: &Rect) {
+        self.push();
+        self.nest_bounds(bounds);
+    }
+
+    /// Create a scope guard, restoring the current bounds and clip area (and popping any
+    /// state pushed within the scope) when the guard is dropped
+    pub fn scope(&mut self) -> Scope<'_, Pixel> {
+        let depth = self.stack.len();
+        Scope {
+            graphics: self,
+            depth,
         }
-        return actual;
     }
 
-    /// Draws a line between two points with the provided pixel style
-    pub fn draw_line(&self, buf: &mut Buff<Pixel>, p1: Point, p2: Point, p: &Pixel) {
+    /// Clip area relative to the current bounds
+    fn local_clip(&self) -> Rect {
+        self.clip.translate(-self.bounds.origin())
+    }
+
+    /// Computes the Cohen-Sutherland outcode for a point against the graphics bounds, extended
+    /// by a pixel on each side so lines with pixels rounding into the bounds are kept
+    fn outcode(&self, x: isize, y: isize) -> u8 {
+        let c = self.local_clip();
+        let mut code = 0;
+
+        if x < c.x - 1 {
+            code |= LEFT;
+        } else if x > c.right() {
+            code |= RIGHT;
+        }
+        if y < c.y - 1 {
+            code |= TOP;
+        } else if y > c.bottom() {
+            code |= BOTTOM;
+        }
+
+        code
+    }
+
+    /// Clips a line to the graphics bounds using the Cohen-Sutherland algorithm,
+    /// returning None if the line is entirely outside the bounds
+    fn clip_line(&self, mut a: Point, mut b: Point) -> Option<(Point, Point)> {
+        let c = self.local_clip();
+        let (min_x, min_y, max_x, max_y) = (c.x - 1, c.y - 1, c.right(), c.bottom());
+        let (mut code_a, mut code_b) = (self.outcode(a.x, a.y), self.outcode(b.x, b.y));
+
+        // Intersection of the line at a given offset along one axis, rounded to the nearest pixel,
+        // widened so the intermediate product can not overflow
+        let lerp = |a: isize, b: isize, num: isize, den: isize| {
+            let (d, num, den) = (b as i128 - a as i128, num as i128, den as i128);
+            let n = 2 * d * num;
+            a + ((n + n.signum() * den.abs()) / (2 * den)) as isize
+        };
+
+        loop {
+            if code_a | code_b == 0 {
+                return Some((a, b));
+            }
+            if code_a & code_b != 0 {
+                return None;
+            }
+
+            // Move the outside end point to the boundary it crosses
+            let code = if code_a != 0 { code_a } else { code_b };
+            let (dx, dy) = (b.x - a.x, b.y - a.y);
+
+            let point = if code & TOP != 0 {
+                Point::new(lerp(a.x, b.x, min_y - a.y, dy), min_y)
+            } else if code & BOTTOM != 0 {
+                Point::new(lerp(a.x, b.x, max_y - a.y, dy), max_y)
+            } else if code & LEFT != 0 {
+                Point::new(min_x, lerp(a.y, b.y, min_x - a.x, dx))
+            } else {
+                Point::new(max_x, lerp(a.y, b.y, max_x - a.x, dx))
+            };
+
+            if code == code_a {
+                a = point;
+                code_a = self.outcode(a.x, a.y);
+            } else {
+                b = point;
+                code_b = self.outcode(b.x, b.y);
+            }
+        }
+    }
+
+    /// Scrolls the contents of a rectangle by the provided offset, filling the exposed area
+    /// The rectangle is limited to the clip area.
+    pub fn scroll(&self, buf: &mut dyn Buff<Pixel>, r: Rect, dx: isize, dy: isize, p: &Pixel) {
+        let (w, h) = buf.size();
+        let visible = self
+            .clip
+            .intersect(&r.translate(self.bounds.origin()))
+            .and_then(|r| r.intersect(&Rect::from_unsigned(0, 0, w, h)));
+
+        if let Some(r) = visible {
+            buf.scroll((r.x as usize, r.y as usize), (r.w, r.h), dx, dy, p);
+        }
+    }
+
+    /// Draws a line between two points with the provided stroke style and pixel style
+    ///
+    /// Lines are clipped to the graphics bounds, and only the visible section is rasterised,
+    /// starting part way along the line so the pixels drawn match those of the unclipped line.
+    pub fn draw_line(
+        &self,
+        buf: &mut dyn Buff<Pixel>,
+        a: Point,
+        b: Point,
+        stroke: &Stroke,
+        p: &Pixel,
+    ) {
+        if stroke.is_hairline() {
+            return self.line(buf, a, b, p);
+        }
+
+        self.stroke_path(buf, &[a, b], false, stroke, p);
+    }
+
+    /// Draws a line between two points using a fill source
+    fn line<F: Paint<Pixel>>(&self, buf: &mut dyn Buff<Pixel>, a: Point, b: Point, p: &F) {
         // Bresenham's line algorithm (https://en.wikipedia.org/wiki/Bresenham%27s_line_algorithm), implementation from:
         // https://www.opengl.org/discussion_boards/showthread.php/168761-Drawing-Line-Bresenhem-midpoint-algorithm
 
-        // Create local mutable copies of the required points
-        let mut a = p1;
-        let mut b = p2;
-
         // Limit points to drawable space
-        //a.x = self::limit(0, self.w, a.x);
-        //a.y = self::limit(0, self.h, a.y);
-        //b.x = self::limit(0, self.w, b.x);
-        //b.y = self::limit(0, self.h, b.y);
+        let (c0, c1) = match self.clip_line(a, b) {
+            Some(l) => l,
+            None => return,
+        };
+
+        // Step along the major axis, accumulating error along the minor axis
+        let (delta_x, delta_y) = (b.x - a.x, b.y - a.y);
+        let x_major = delta_x.abs() > delta_y.abs();
+        let (major, minor) = if x_major {
+            (delta_x, delta_y)
+        } else {
+            (delta_y, delta_x)
+        };
+        let (sign_major, sign_minor) = (major.signum(), minor.signum());
+        let (major, minor) = (major.abs(), minor.abs());
+
+        // Range of steps covering the clipped line, extended by one to allow for clip rounding
+        let step = |c: Point| {
+            if x_major {
+                (c.x - a.x).abs()
+            } else {
+                (c.y - a.y).abs()
+            }
+        };
+        let first = (step(c0).min(step(c1)) - 1).max(0);
+        let last = (step(c0).max(step(c1)) + 1).min(major);
+
+        // Error and minor axis offset after the skipped steps
+        let start = major / 2;
+        let mut offset = if first * minor > start {
+            (first * minor - start + major - 1) / major
+        } else {
+            0
+        };
+        let mut accum = start - first * minor + offset * major;
 
-        let mut delta_x = b.x as isize - a.x as isize;
-        let mut delta_y = b.y as isize - a.y as isize;
+        for i in first..=last {
+            if x_major {
+                self.plot(buf, a.x + i * sign_major, a.y + offset * sign_minor, p);
+            } else {
+                self.plot(buf, a.x + offset * sign_minor, a.y + i * sign_major, p);
+            }
 
-        let sign_x = delta_x.signum();
-        let sign_y = delta_y.signum();
+            accum -= minor;
+            if accum < 0 {
+                accum += major;
+                offset += 1;
+            }
+        }
+    }
 
-        delta_x = delta_x.abs();
-        delta_y = delta_y.abs();
+    /// Draws a rectange with the provided stroke style and pixel style, with wider strokes
+    /// centred on the single pixel outline
+    pub fn draw_rect(&self, b: &mut dyn Buff<Pixel>, r: Rect, stroke: &Stroke, p: &Pixel) {
+        let (right, bottom) = (r.x + r.w as isize, r.y + r.h as isize);
 
-        b.x = (b.x as isize + sign_x) as usize;
-        b.y = (b.y as isize + sign_y) as usize;
+        if stroke.is_hairline() {
+            self.hline(b, r.x, right, r.y, p);
+            self.hline(b, r.x, right, bottom, p);
+            self.vline(b, r.x, r.y, bottom, p);
+            self.vline(b, right, r.y, bottom, p);
+            return;
+        }
 
-        self.set(buf, a.x, a.y, p);
+        let points = [
+            Point::new(r.x, r.y),
+            Point::new(right, r.y),
+            Point::new(right, bottom),
+            Point::new(r.x, bottom),
+        ];
 
-        if delta_x > delta_y {
-            let mut accum = delta_x / 2;
-            loop {
-                self.set(buf, a.x, a.y, p);
-                accum -= delta_y;
-                if accum < 0 {
-                    accum += delta_x;
-                    a.y = (a.y as isize + sign_y) as usize;
-                }
-                a.x = (a.x as isize + sign_x) as usize;
+        self.stroke_path(b, &points, true, stroke, p);
+    }
+
+    /// Fills a rectangle using the provided fill source
+    pub fn fill_rect<F: Paint<Pixel>>(&self, b: &mut dyn Buff<Pixel>, r: Rect, p: &F) {
+        for y in r.y..r.y + r.h as isize {
+            self.hline(b, r.x, r.x + r.w as isize - 1, y, p);
+        }
+    }
+
+    /// Draws a polyline connecting a list of points with the provided stroke style
+    pub fn draw_polyline(
+        &self,
+        b: &mut dyn Buff<Pixel>,
+        points: &[Point],
+        stroke: &Stroke,
+        p: &Pixel,
+    ) {
+        if !stroke.is_hairline() {
+            return self.stroke_path(b, points, false, stroke, p);
+        }
+
+        for w in points.windows(2) {
+            self.line(b, w[0], w[1], p);
+        }
+    }
+
+    /// Draws a horizontal span between two x coordinates (inclusive), clipped to the graphics bounds
+    fn hline<F: Paint<Pixel>>(
+        &self,
+        b: &mut dyn Buff<Pixel>,
+        x0: isize,
+        x1: isize,
+        y: isize,
+        p: &F,
+    ) {
+        let c = self.local_clip();
+        if y < c.y || y >= c.bottom() {
+            return;
+        }
+
+        let start = x0.min(x1).max(c.x);
+        let end = x0.max(x1).min(c.right() - 1);
+
+        for x in start..=end {
+            self.plot(b, x, y, p);
+        }
+    }
+
+    /// Draws a vertical span between two y coordinates (inclusive), clipped to the graphics bounds
+    fn vline(&self, b: &mut dyn Buff<Pixel>, x: isize, y0: isize, y1: isize, p: &Pixel) {
+        let c = self.local_clip();
+        if x < c.x || x >= c.right() {
+            return;
+        }
+
+        let start = y0.min(y1).max(c.y);
+        let end = y0.max(y1).min(c.bottom() - 1);
+
+        for y in start..=end {
+            self.set(b, x, y, p);
+        }
+    }
+
+    /// Midpoint ellipse algorithm over the provided (inclusive) bounds, calling `f` with the
+    /// left and right x coordinates and top and bottom y coordinates of each step
+    fn ellipse<F>(left: isize, top: isize, right: isize, bottom: isize, mut f: F)
+    where
+        F: FnMut(isize, isize, isize, isize),
+    {
+        // Implementation also from:
+        // https://www.opengl.org/discussion_boards/showthread.php/168761-Drawing-Line-Bresenhem-midpoint-algorithm
+
+        let a = (right - left + 1) / 2;
+        let b = (bottom - top + 1) / 2;
+
+        if a == 0 || b == 0 {
+            return;
+        }
+
+        let a2 = a * a;
+        let b2 = b * b;
+        let two_a2 = a2 * 2;
+        let two_b2 = b2 * 2;
+        let four_a2 = a2 * 4;
+        let four_b2 = b2 * 4;
+        let mut x = 0;
+        let mut y = b;
+        let mut s = a2 * (1 - (b * 2)) + two_b2;
+        let mut t = b2 - two_a2 * ((b * 2) - 1);
+
+        f(left - x + a, right + x - a, top - y + b, bottom + y - b);
+
+        loop {
+            if s < 0 {
+                s += two_b2 * ((x << 1) + 3);
+                t += four_b2 * (x + 1);
+                x += 1;
+            } else if t < 0 {
+                s += two_b2 * ((x << 1) + 3) - four_a2 * (y - 1);
+                t += four_b2 * (x + 1) - two_a2 * ((y << 1) - 3);
+                x += 1;
+                y -= 1;
+            } else {
+                s -= four_a2 * (y - 1);
+                t -= two_a2 * ((y << 1) - 3);
+                y -= 1;
+            }
+
+            f(left - x + a, right + x - a, top - y + b, bottom + y - b);
+
+            if y <= 0 {
+                break;
+            }
+        }
+    }
+
+    /// Draws a closed polygon connecting a list of points
+    pub fn draw_polygon(&self, b: &mut dyn Buff<Pixel>, points: &[Point], p: &Pixel) {
+        self.outline(b, points, p);
+    }
+
+    /// Draws a closed polygon outline using a fill source
+    fn outline<F: Paint<Pixel>>(&self, b: &mut dyn Buff<Pixel>, points: &[Point], p: &F) {
+        if let (Some(first), Some(last)) = (points.first(), points.last()) {
+            for w in points.windows(2) {
+                self.line(b, w[0], w[1], p);
+            }
+            self.line(b, *last, *first, p);
+        }
+    }
+
+    /// Fills a polygon using the provided fill rule and fill source, including the outline
+    /// drawn by `draw_polygon`
+    pub fn fill_polygon<F: Paint<Pixel>>(
+        &self,
+        b: &mut dyn Buff<Pixel>,
+        points: &[Point],
+        rule: FillRule,
+        p: &F,
+    ) {
+        let vertices: Vec<(isize, isize)> = points.iter().map(|v| (v.x, v.y)).collect();
+        let c = self.local_clip();
+
+        let (top, bottom) = match (
+            vertices.iter().map(|v| v.1).min(),
+            vertices.iter().map(|v| v.1).max(),
+        ) {
+            (Some(t), Some(b)) => (t.max(c.y), b.min(c.bottom() - 1)),
+            _ => return,
+        };
+
+        // Edge crossings (x, winding direction) for the current scanline
+        let mut crossings = Vec::with_capacity(vertices.len());
+
+        for y in top..=bottom {
+            crossings.clear();
+
+            for (i, &a) in vertices.iter().enumerate() {
+                let c = vertices[(i + 1) % vertices.len()];
+                let (v0, v1, dir) = if a.1 < c.1 { (a, c, 1) } else { (c, a, -1) };
 
-                if a.x == b.x {
-                    break;
+                // Each edge covers rows y0 <= y < y1, so shared vertices are only counted once
+                if v0.1 <= y && y < v1.1 {
+                    let dy = v1.1 - v0.1;
+                    let num = (y - v0.1) * (v1.0 - v0.0) * 2 + dy;
+                    crossings.push((v0.0 + num.div_euclid(2 * dy), dir));
                 }
             }
-        } else {
-            let mut accum = delta_y / 2;
-            loop {
-                self.set(buf, a.x, a.y, p);
-
-                accum -= delta_x;
-                if accum < 0 {
-                    accum += delta_y;
-                    a.x = (a.x as isize + sign_x) as usize;
+
+            crossings.sort_unstable_by_key(|c| c.0);
+
+            let mut winding = 0;
+            for (i, &(x, dir)) in crossings.iter().enumerate() {
+                winding += dir;
+
+                let inside = match rule {
+                    FillRule::EvenOdd => i % 2 == 0,
+                    FillRule::NonZero => winding != 0,
+                };
+
+                // Spans include the edge pixels, matching the outline drawn by `draw_line`
+                if let (true, Some(&(next, _))) = (inside, crossings.get(i + 1)) {
+                    self.hline(b, x, next, y, p);
                 }
-                a.y = (a.y as isize + sign_y) as usize;
+            }
+        }
+
+        self.outline(b, points, p);
+    }
+
+    /// Fills a triangle between three points using the provided fill source, including the
+    /// outline drawn by `draw_polygon`
+    pub fn fill_triangle<F: Paint<Pixel>>(
+        &self,
+        b: &mut dyn Buff<Pixel>,
+        p0: Point,
+        p1: Point,
+        p2: Point,
+        p: &F,
+    ) {
+        let v = [(p0.x, p0.y), (p1.x, p1.y), (p2.x, p2.y)];
+        let edges = [(v[0], v[1]), (v[1], v[2]), (v[2], v[0])];
+
+        let c = self.local_clip();
+        let top = v[0].1.min(v[1].1).min(v[2].1).max(c.y);
+        let bottom = v[0].1.max(v[1].1).max(v[2].1).min(c.bottom() - 1);
 
-                if a.y == b.y {
-                    break;
+        for y in top..=bottom {
+            let (mut left, mut right) = (isize::MAX, isize::MIN);
+
+            // Extent of all edges covering this row, horizontal edges cover both end points
+            for &(a, c) in &edges {
+                let ((x0, y0), (x1, y1)) = if a.1 <= c.1 { (a, c) } else { (c, a) };
+                if y < y0 || y > y1 {
+                    continue;
                 }
+
+                let (xa, xb) = if y0 == y1 {
+                    (x0.min(x1), x0.max(x1))
+                } else {
+                    let dy = y1 - y0;
+                    let x = x0 + ((y - y0) * (x1 - x0) * 2 + dy).div_euclid(2 * dy);
+                    (x, x)
+                };
+
+                left = left.min(xa);
+                right = right.max(xb);
+            }
+
+            if left <= right {
+                self.hline(b, left, right, y, p);
             }
         }
+
+        self.outline(b, &[p0, p1, p2], p);
+    }
+
+    /// Draws an ellipse outline over the provided (inclusive) bounds
+    fn stroke_ellipse(&self, buf: &mut dyn Buff<Pixel>, bounds: [isize; 4], p: &Pixel) {
+        let [left, top, right, bottom] = bounds;
+
+        Self::ellipse(left, top, right, bottom, |x0, x1, y0, y1| {
+            self.set(buf, x1, y1, p);
+            self.set(buf, x0, y1, p);
+            self.set(buf, x0, y0, p);
+            self.set(buf, x1, y0, p);
+        });
+    }
+
+    /// Fills an ellipse over the provided (inclusive) bounds
+    fn fill_ellipse_bounds<F: Paint<Pixel>>(
+        &self,
+        buf: &mut dyn Buff<Pixel>,
+        bounds: [isize; 4],
+        p: &F,
+    ) {
+        let [left, top, right, bottom] = bounds;
+
+        Self::ellipse(left, top, right, bottom, |x0, x1, y0, y1| {
+            self.hline(buf, x0, x1, y0, p);
+            self.hline(buf, x0, x1, y1, p);
+        });
     }
 
-    /// Draws a rectange with the provided pixel style
-    pub fn draw_rect(&self, b: &mut Buff<Pixel>, r: Rect, p: &Pixel) {
-        for x in 0..r.w {
-            self.set(b, r.x + x, r.y, p);
-            self.set(b, r.x + x, r.y + r.h, p);
+    /// Draws an ellipse to fill the provided rectangle with the provided stroke style, with
+    /// wider strokes centred on the single pixel outline
+    pub fn draw_ellipse(&self, buf: &mut dyn Buff<Pixel>, r: Rect, stroke: &Stroke, p: &Pixel) {
+        if stroke.is_hairline() {
+            let (right, bottom) = (r.x + r.w as isize, r.y + r.h as isize);
+            return self.stroke_ellipse(buf, [r.x, r.y, right, bottom], p);
         }
-        for y in 0..r.h {
-            self.set(b, r.x, r.y + y, p);
-            self.set(b, r.x + r.w, r.y + y, p);
+
+        // Approximate the ellipse with a polygon, using segments of around four pixels
+        let (w, h) = (r.w as isize, r.h as isize);
+        let steps = ((w + h) * 3 / 4).clamp(8, 360);
+        let one = trig::ONE;
+
+        let points: Vec<Point> = (0..steps)
+            .map(|i| {
+                let deg = i * 360 / steps;
+                Point::new(
+                    r.x + (w * (one + trig::cos(deg)) + one) / (2 * one),
+                    r.y + (h * (one + trig::sin(deg)) + one) / (2 * one),
+                )
+            })
+            .collect();
+
+        self.stroke_path(buf, &points, true, stroke, p);
+    }
+
+    /// Fills an ellipse within the provided rectangle using the provided fill source, matching
+    /// the outline of `draw_ellipse`
+    pub fn fill_ellipse<F: Paint<Pixel>>(&self, buf: &mut dyn Buff<Pixel>, r: Rect, p: &F) {
+        let (right, bottom) = (r.x + r.w as isize, r.y + r.h as isize);
+        self.fill_ellipse_bounds(buf, [r.x, r.y, right, bottom], p);
+    }
+
+    /// Draws a circle outline with the provided centre and radius
+    pub fn draw_circle(&self, buf: &mut dyn Buff<Pixel>, centre: Point, radius: usize, p: &Pixel) {
+        let (x, y, r) = (centre.x, centre.y, radius as isize);
+        self.stroke_ellipse(buf, [x - r, y - r, x + r, y + r], p);
+    }
+
+    /// Fills a circle with the provided centre and radius using the provided fill source,
+    /// matching the outline of `draw_circle`
+    pub fn fill_circle<F: Paint<Pixel>>(
+        &self,
+        buf: &mut dyn Buff<Pixel>,
+        centre: Point,
+        radius: usize,
+        p: &F,
+    ) {
+        let (x, y, r) = (centre.x, centre.y, radius as isize);
+        self.fill_ellipse_bounds(buf, [x - r, y - r, x + r, y + r], p);
+    }
+
+    /// Computes corner circle centres (left, top, right, bottom) for a rounded rectangle,
+    /// limiting the radius to fit the rectangle
+    fn round_rect_corners(r: Rect, radius: usize) -> (isize, [isize; 4]) {
+        let radius = radius.min(r.w / 2).min(r.h / 2) as isize;
+        let (x, y) = (r.x, r.y);
+        let (w, h) = (r.w as isize, r.h as isize);
+
+        (
+            radius,
+            [x + radius, y + radius, x + w - radius, y + h - radius],
+        )
+    }
+
+    /// Draws a rectangle outline with corners rounded to the provided radius
+    pub fn draw_round_rect(&self, buf: &mut dyn Buff<Pixel>, r: Rect, radius: usize, p: &Pixel) {
+        let (radius, [left, top, right, bottom]) = Self::round_rect_corners(r, radius);
+
+        self.hline(buf, left, right, top - radius, p);
+        self.hline(buf, left, right, bottom + radius, p);
+        self.vline(buf, left - radius, top, bottom, p);
+        self.vline(buf, right + radius, top, bottom, p);
+
+        Self::ellipse(-radius, -radius, radius, radius, |x0, x1, y0, y1| {
+            self.set(buf, left + x0, top + y0, p);
+            self.set(buf, right + x1, top + y0, p);
+            self.set(buf, left + x0, bottom + y1, p);
+            self.set(buf, right + x1, bottom + y1, p);
+        });
+    }
+
+    /// Fills a rectangle with corners rounded to the provided radius, using the provided fill source
+    pub fn fill_round_rect<F: Paint<Pixel>>(
+        &self,
+        buf: &mut dyn Buff<Pixel>,
+        r: Rect,
+        radius: usize,
+        p: &F,
+    ) {
+        let (radius, [left, top, right, bottom]) = Self::round_rect_corners(r, radius);
+
+        for y in top..=bottom {
+            self.hline(buf, left - radius, right + radius, y, p);
         }
+
+        Self::ellipse(-radius, -radius, radius, radius, |x0, x1, y0, y1| {
+            self.hline(buf, left + x0, right + x1, top + y0, p);
+            self.hline(buf, left + x0, right + x1, bottom + y1, p);
+        });
     }
 
-    /// Draws a rectangle with the provided pixel style
-    pub fn fill_rect(&self, b: &mut Buff<Pixel>, r: Rect, p: &Pixel) {
-        for y in 0..r.h {
-            for x in 0..r.w {
-                self.set(b, r.x + x, r.y + y, p);
+    /// Draws a circular arc with the provided centre and radius, from the start angle clockwise
+    /// to the end angle (in degrees, with zero along the positive x axis)
+    pub fn draw_arc(
+        &self,
+        buf: &mut dyn Buff<Pixel>,
+        centre: Point,
+        radius: usize,
+        start: isize,
+        end: isize,
+        p: &Pixel,
+    ) {
+        let (cx, cy, r) = (centre.x, centre.y, radius as isize);
+        let sector = Sector::new(start, end);
+
+        Self::ellipse(-r, -r, r, r, |x0, x1, y0, y1| {
+            for &(x, y) in &[(x1, y1), (x0, y1), (x0, y0), (x1, y0)] {
+                if sector.contains(x, y) {
+                    self.set(buf, cx + x, cy + y, p);
+                }
+            }
+        });
+    }
+
+    /// Fills a pie segment with the provided centre and radius, from the start angle clockwise
+    /// to the end angle (in degrees, with zero along the positive x axis)
+    pub fn fill_pie<F: Paint<Pixel>>(
+        &self,
+        buf: &mut dyn Buff<Pixel>,
+        centre: Point,
+        radius: usize,
+        start: isize,
+        end: isize,
+        p: &F,
+    ) {
+        self.fill_annulus(buf, centre, radius, 0, start, end, p);
+    }
+
+    /// Fills a segment of a ring between the inner and outer radii, from the start angle clockwise
+    /// to the end angle (in degrees, with zero along the positive x axis)
+    ///
+    /// The outer edge matches `draw_arc`, and the inner edge includes the pixels of an arc
+    /// drawn at the inner radius.
+    #[allow(clippy::too_many_arguments)]
+    pub fn fill_annulus<F: Paint<Pixel>>(
+        &self,
+        buf: &mut dyn Buff<Pixel>,
+        centre: Point,
+        outer: usize,
+        inner: usize,
+        start: isize,
+        end: isize,
+        p: &F,
+    ) {
+        let (cx, cy, r) = (centre.x, centre.y, outer as isize);
+        let sector = Sector::new(start, end);
+
+        // Pixels strictly within the inner circle outline are excluded
+        let inner = inner.min(outer) as isize;
+        let hole = inner * inner - inner;
+
+        let mut span = |x0: isize, x1: isize, y: isize| {
+            for x in x0..=x1 {
+                let in_hole = inner > 0 && x * x + y * y <= hole;
+                if !in_hole && sector.contains(x, y) {
+                    self.plot(buf, cx + x, cy + y, p);
+                }
+            }
+        };
+
+        let mut last = None;
+        Self::ellipse(-r, -r, r, r, |x0, x1, y0, y1| {
+            // Rows repeat while stepping along the flat top and bottom, draw the widest span
+            if let Some((lx0, lx1, ly0, ly1)) = last {
+                if ly0 != y0 {
+                    span(lx0, lx1, ly0);
+                    if ly1 != ly0 {
+                        span(lx0, lx1, ly1);
+                    }
+                }
+            }
+            last = Some((x0, x1, y0, y1));
+        });
+
+        if let Some((x0, x1, y0, y1)) = last {
+            span(x0, x1, y0);
+            if y1 != y0 {
+                span(x0, x1, y1);
             }
         }
     }
 
-    /// Draws a polyline connecting a list of points
-    pub fn draw_polyline(&self, b: &mut Buff<Pixel>, points: &[Point], p: &Pixel) {
-        let len = points.len();
-        for i in 0..len - 1 {
-            let p1 = points[i];
-            let p2 = points[i + 1];
-            self.draw_line(b, p1, p2, p);
+    /// Strokes a path through a list of points, applying dashes, caps and joins
+    fn stroke_path(
+        &self,
+        buf: &mut dyn Buff<Pixel>,
+        points: &[Point],
+        closed: bool,
+        stroke: &Stroke,
+        p: &Pixel,
+    ) {
+        let n = points.len();
+        if stroke.width == 0 || n == 0 {
+            return;
+        }
+
+        let segments = if closed { n } else { n - 1 };
+        let mut dasher = Dasher::new(stroke.dash);
+
+        // Normals of the first segment (if drawn at its start) and the previous segment
+        // (if drawn at its end), used for joins
+        let mut first = None;
+        let mut prev = None;
+
+        for i in 0..segments {
+            let (a, b) = (points[i], points[(i + 1) % n]);
+            let (dx, dy) = (b.x - a.x, b.y - a.y);
+            let len = isqrt(dx * dx + dy * dy);
+            if len == 0 {
+                continue;
+            }
+
+            // Offsets from the centre of the line to each edge, split unevenly for even widths.
+            // The length is kept to four fractional bits so the rounded offsets stay
+            // perpendicular, rather than extending the edges past the end points.
+            let w = (stroke.width as isize - 1) << 4;
+            let flen = isqrt((dx * dx + dy * dy) << 8);
+            let full = Point::new(
+                (-dy * w * 2 + flen).div_euclid(2 * flen),
+                (dx * w * 2 + flen).div_euclid(2 * flen),
+            );
+            let plus = Point::new((full.x + 1).div_euclid(2), (full.y + 1).div_euclid(2));
+            let normal = (plus, full - plus);
+
+            // Caps are drawn at the ends of dashes and open paths, joins elsewhere
+            let cap_start = !closed && i == 0;
+            let cap_end = !closed && i == segments - 1;
+
+            let (mut starts_on, mut ends_on) = (false, false);
+            dasher.runs(len as usize, |s, e, on| {
+                let (s, e) = (s as isize, e as isize);
+                if s == 0 {
+                    starts_on = on;
+                }
+                if e == len {
+                    ends_on = on;
+                }
+                if on {
+                    // Dashes cover pixels from their start up to their end, or the segment end
+                    let end = if e < len { (e - 1).max(s) } else { e };
+                    let at = |d: isize| {
+                        Point::new(
+                            a.x + (dx * d * 2 + len).div_euclid(2 * len),
+                            a.y + (dy * d * 2 + len).div_euclid(2 * len),
+                        )
+                    };
+                    self.stroke_segment(
+                        buf,
+                        (at(s), at(end)),
+                        normal,
+                        stroke,
+                        (s > 0 || cap_start, e < len || cap_end),
+                        p,
+                    );
+                }
+            });
+
+            match prev {
+                Some(n1) if starts_on => self.stroke_join(buf, a, n1, normal, stroke, p),
+                _ => (),
+            }
+            if i == 0 && starts_on {
+                first = Some(normal);
+            }
+            prev = if ends_on { Some(normal) } else { None };
+        }
+
+        if let (true, Some(n1), Some(n2)) = (closed, prev, first) {
+            self.stroke_join(buf, points[0], n1, n2, stroke, p);
         }
     }
 
-    /// Draws an ellipse to fill the provided rectangle
-    pub fn draw_ellipse(&self, buf: &mut Buff<Pixel>, r: Rect, p: &Pixel) {
-        // Implementation also from:
-        // https://www.opengl.org/discussion_boards/showthread.php/168761-Drawing-Line-Bresenhem-midpoint-algorithm
+    /// Strokes a single straight segment, with the provided offsets from the centre line
+    /// to each edge and flags for whether caps are drawn at the start and end
+    fn stroke_segment(
+        &self,
+        buf: &mut dyn Buff<Pixel>,
+        line: (Point, Point),
+        normal: (Point, Point),
+        stroke: &Stroke,
+        caps: (bool, bool),
+        p: &Pixel,
+    ) {
+        let (mut a, mut b) = line;
 
-        let left = (r.x) as isize;
-        let right = (r.x + r.w) as isize;
-        let top = (r.y) as isize;
-        let bottom = (r.y + r.h) as isize;
-
-        let a = ((right - left + 1) / 2) as isize;
-        let b = ((bottom - top + 1) / 2) as isize;
-
-        if a != 0 && b != 0 {
-            let a2 = (a * a) as isize;
-            let b2 = (b * b) as isize;
-            let two_a2 = (a2 * 2) as isize;
-            let two_b2 = (b2 * 2) as isize;
-            let four_a2 = (a2 * 4) as isize;
-            let four_b2 = (b2 * 4) as isize;
-            let mut x = 0 as isize;
-            let mut y = b as isize;
-            let mut s = a2 * (1 - (b * 2)) + two_b2;
-            let mut t = b2 - two_a2 * ((b * 2) - 1);
-
-            self.set(buf, (right + x - a) as usize, (bottom + y - b) as usize, p);
-            self.set(buf, (left - x + a) as usize, (bottom + y - b) as usize, p);
-            self.set(buf, (left - x + a) as usize, (top - y + b) as usize, p);
-            self.set(buf, (right + x - a) as usize, (top - y + b) as usize, p);
-
-            loop {
-                if s < 0 {
-                    s += two_b2 * ((x << 1) + 3);
-                    t += four_b2 * (x + 1);
-                    x += 1;
-                } else if t < 0 {
-                    s += two_b2 * ((x << 1) + 3) - four_a2 * (y - 1);
-                    t += four_b2 * (x + 1) - two_a2 * ((y << 1) - 3);
-                    x += 1;
-                    y -= 1;
-                } else {
-                    s -= four_a2 * (y - 1);
-                    t -= two_a2 * ((y << 1) - 3);
-                    y -= 1;
+        if stroke.width <= 1 {
+            return self.line(buf, a, b, p);
+        }
+
+        // Thick lines are filled as polygons, with caps extending past the end points
+        let (plus, minus) = normal;
+        match stroke.cap {
+            Cap::Butt => (),
+            Cap::Square => {
+                if caps.0 {
+                    a -= Point::new(plus.y, -plus.x);
+                }
+                if caps.1 {
+                    b += Point::new(minus.y, -minus.x);
+                }
+            }
+            Cap::Round => {
+                let radius = (stroke.width - 1) / 2;
+                if caps.0 {
+                    self.fill_circle(buf, a, radius, p);
                 }
+                if caps.1 {
+                    self.fill_circle(buf, b, radius, p);
+                }
+            }
+        }
 
-                self.set(buf, (right + x - a) as usize, (bottom + y - b) as usize, p);
-                self.set(buf, (left - x + a) as usize, (bottom + y - b) as usize, p);
-                self.set(buf, (left - x + a) as usize, (top - y + b) as usize, p);
-                self.set(buf, (right + x - a) as usize, (top - y + b) as usize, p);
+        let corners = [a + plus, b + plus, b - minus, a - minus];
+        self.fill_polygon(buf, &corners, FillRule::NonZero, p);
+    }
 
-                if y <= 0 {
-                    break;
+    /// Joins two thick segments at a vertex, using round joins for round caps and
+    /// bevel joins otherwise
+    fn stroke_join(
+        &self,
+        buf: &mut dyn Buff<Pixel>,
+        v: Point,
+        n1: (Point, Point),
+        n2: (Point, Point),
+        stroke: &Stroke,
+        p: &Pixel,
+    ) {
+        if stroke.width <= 1 {
+            return;
+        }
+
+        match stroke.cap {
+            Cap::Round => self.fill_circle(buf, v, (stroke.width - 1) / 2, p),
+            _ => {
+                self.fill_triangle(buf, v, v + n1.0, v + n2.0, p);
+                self.fill_triangle(buf, v, v - n1.1, v - n2.1, p);
+            }
+        }
+    }
+
+    /// Draws a character from the provided font, with the top left of the text line at the
+    /// provided point. Returns the horizontal advance for the character.
+    pub fn draw_char<F: Glyphs>(
+        &self,
+        b: &mut dyn Buff<Pixel>,
+        pos: Point,
+        font: &F,
+        c: char,
+        p: &Pixel,
+    ) -> isize {
+        let ch = match font.glyph(c) {
+            Some(ch) => ch,
+            None => return 0,
+        };
+
+        // Bitmap is positioned relative to the pen on the baseline
+        let left = pos.x + ch.bearing_x as isize;
+        let top = pos.y + font.ascent() as isize - ch.bearing_y as isize;
+
+        for y in 0..ch.height as usize {
+            for x in 0..ch.width as usize {
+                if ch.get(x, y) {
+                    self.set(b, left + x as isize, top + y as isize, p);
                 }
             }
         }
+
+        ch.advance as isize
+    }
+
+    /// Draws a string using the provided font, with the top left of the first line at the
+    /// provided point. Newlines start a new line, characters missing from the font are skipped.
+    pub fn draw_text<F: Glyphs>(
+        &self,
+        b: &mut dyn Buff<Pixel>,
+        pos: Point,
+        font: &F,
+        text: &str,
+        p: &Pixel,
+    ) {
+        let mut pen = pos;
+
+        for c in text.chars() {
+            if c == '\n' {
+                pen.x = pos.x;
+                pen.y += font.height() as isize;
+                continue;
+            }
+
+            pen.x += self.draw_char(b, pen, font, c, p);
+        }
+    }
+
+    /// Draws a character from a stroke font scaled to the provided cap height (in pixels),
+    /// with the top left of the text line at the provided point. Strokes are centred on the
+    /// character path. Returns the horizontal advance for the character.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_stroke_char(
+        &self,
+        b: &mut dyn Buff<Pixel>,
+        pos: Point,
+        font: &StrokeFont,
+        size: usize,
+        c: char,
+        stroke: &Stroke,
+        p: &Pixel,
+    ) -> isize {
+        let ch = match font.get(c) {
+            Some(ch) => ch,
+            None => return 0,
+        };
+
+        let scale = |(x, y): (u8, u8)| {
+            Point::new(
+                pos.x + font.scale(x as isize, size),
+                pos.y + font.scale(y as isize, size),
+            )
+        };
+
+        for s in ch.strokes() {
+            let points: Vec<Point> = s.map(scale).collect();
+
+            match (points.len(), stroke.width) {
+                (1, w) if w <= 1 => self.set(b, points[0].x, points[0].y, p),
+                (1, w) => self.fill_circle(b, points[0], (w - 1) / 2, p),
+                _ => self.draw_polyline(b, &points, stroke, p),
+            }
+        }
+
+        font.advance(ch, size)
+    }
+
+    /// Draws a string using a stroke font scaled to the provided cap height (in pixels), with
+    /// the top left of the first line at the provided point. Newlines start a new line,
+    /// characters missing from the font are skipped.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_stroke_text(
+        &self,
+        b: &mut dyn Buff<Pixel>,
+        pos: Point,
+        font: &StrokeFont,
+        size: usize,
+        text: &str,
+        stroke: &Stroke,
+        p: &Pixel,
+    ) {
+        let mut pen = pos;
+
+        for c in text.chars() {
+            if c == '\n' {
+                pen.x = pos.x;
+                pen.y += font.scale(font.line_height as isize, size);
+                continue;
+            }
+
+            pen.x += self.draw_stroke_char(b, pen, font, size, c, stroke, p);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use self::fill::Fill;
+    use crate::core::buffer::Buffer;
+    use crate::fonts::{Char, Font, SIMPLEX};
+    use crate::graphics::test_utils::{bw_buffer, render, H, W};
+    use crate::types::pixel::PixelBW;
+
+    const FONT: Font = Font {
+        size: 4,
+        height: 5,
+        ascent: 4,
+        chars: &[
+            Char {
+                code: 'i',
+                width: 1,
+                height: 3,
+                advance: 2,
+                bearing_x: 0,
+                bearing_y: 3,
+                data: &[0b1000_0000, 0b0000_0000, 0b1000_0000],
+            },
+            Char {
+                code: 'j',
+                width: 2,
+                height: 4,
+                advance: 3,
+                bearing_x: -1,
+                bearing_y: 3,
+                data: &[0b0100_0000, 0b0000_0000, 0b0100_0000, 0b1000_0000],
+            },
+        ],
+    };
+
+    #[test]
+    fn test_draw_text() {
+        let mut data = [0u8; W * H / 8];
+        let mut buffer = bw_buffer(&mut data);
+        let graphics = Graphics::new(0, 0, W, H);
+
+        graphics.draw_text(&mut buffer, Point::new(0, 0), &FONT, "ij?\nji", &true);
+
+        let expected = [
+            "................",
+            "#.#.............",
+            "................",
+            "#.#.............",
+            ".#..............",
+            "................",
+            "#..#............",
+            "................",
+        ];
+        assert_eq!(render(&buffer), expected);
+    }
+
+    #[test]
+    fn test_draw_stroke_text() {
+        let mut data = [0u8; W * H / 8];
+        let mut buffer = bw_buffer(&mut data);
+        let graphics = Graphics::new(0, 0, W, H);
+
+        graphics.draw_stroke_text(
+            &mut buffer,
+            Point::new(0, 0),
+            &SIMPLEX,
+            4,
+            "7l",
+            &Stroke::new(1),
+            &true,
+        );
+        graphics.draw_stroke_text(
+            &mut buffer,
+            Point::new(8, 0),
+            &SIMPLEX,
+            6,
+            "-.",
+            &Stroke::new(3).cap(Cap::Round),
+            &true,
+        );
+
+        let expected = [
+            "#####...........",
+            "...##...........",
+            "..#.#...####....",
+            "..#.#..######...",
+            ".#..########....",
+            ".............#..",
+            "............###.",
+            ".............#..",
+        ];
+        assert_eq!(render(&buffer), expected);
+    }
+
+    #[test]
+    fn test_fill_circle() {
+        let mut outline = [0u8; W * H / 8];
+        let mut filled = [0u8; W * H / 8];
+        let graphics = Graphics::new(0, 0, W, H);
+
+        let mut buffer = bw_buffer(&mut outline);
+        graphics.draw_circle(&mut buffer, Point::new(5, 3), 3, &true);
+        let outline = render(&buffer);
+
+        let mut buffer = bw_buffer(&mut filled);
+        graphics.fill_circle(&mut buffer, Point::new(5, 3), 3, &true);
+        let filled = render(&buffer);
+
+        let expected = [
+            "....###.........",
+            "...#####........",
+            "..#######.......",
+            "..#######.......",
+            "..#######.......",
+            "...#####........",
+            "....###.........",
+            "................",
+        ];
+        assert_eq!(filled, expected);
+
+        // Fill spans exactly between the outline pixels on each row
+        for (o, f) in outline.iter().zip(filled.iter()) {
+            assert_eq!(o.find('#'), f.find('#'));
+            assert_eq!(o.rfind('#'), f.rfind('#'));
+        }
+    }
+
+    #[test]
+    fn test_fill_ellipse_clipped() {
+        let mut data = [0u8; W * H / 8];
+        let mut buffer = bw_buffer(&mut data);
+        let graphics = Graphics::new(2, 2, 4, 4);
+
+        // Ellipse extends past the bounds on all sides
+        graphics.fill_ellipse(&mut buffer, Rect::new(0, 0, 7, 5), &true);
+        graphics.fill_circle(&mut buffer, Point::new(0, 0), 6, &true);
+
+        let expected = [
+            "................",
+            "................",
+            "..####..........",
+            "..####..........",
+            "..####..........",
+            "..####..........",
+            "................",
+            "................",
+        ];
+        assert_eq!(render(&buffer), expected);
+    }
+
+    #[test]
+    fn test_round_rect() {
+        let mut outline = [0u8; W * H / 8];
+        let mut filled = [0u8; W * H / 8];
+        let graphics = Graphics::new(0, 0, W, H);
+        let r = Rect::new(1, 1, 10, 5);
+
+        let mut buffer = bw_buffer(&mut outline);
+        graphics.draw_round_rect(&mut buffer, r, 2, &true);
+        let outline = render(&buffer);
+
+        let mut buffer = bw_buffer(&mut filled);
+        graphics.fill_round_rect(&mut buffer, r, 2, &true);
+        let filled = render(&buffer);
+
+        let expected = [
+            "................",
+            "..#########.....",
+            ".#.........#....",
+            ".#.........#....",
+            ".#.........#....",
+            ".#.........#....",
+            "..#########.....",
+            "................",
+        ];
+        assert_eq!(outline, expected);
+
+        // Fill spans exactly between the outline pixels on each row
+        for (o, f) in outline.iter().zip(filled.iter()) {
+            assert_eq!(o.find('#'), f.find('#'));
+            assert_eq!(o.rfind('#'), f.rfind('#'));
+            assert!(!f.trim_matches('.').contains('.'));
+        }
+    }
+
+    #[test]
+    fn test_arc_pie() {
+        let mut arc = [0u8; W * H / 8];
+        let mut pie = [0u8; W * H / 8];
+        let graphics = Graphics::new(0, 0, W, H);
+
+        let mut buffer = bw_buffer(&mut arc);
+        graphics.draw_arc(&mut buffer, Point::new(7, 3), 3, 180, 360, &true);
+        graphics.draw_arc(&mut buffer, Point::new(7, 3), 3, 45, 135, &true);
+        let arc = render(&buffer);
+
+        let mut buffer = bw_buffer(&mut pie);
+        graphics.fill_pie(&mut buffer, Point::new(3, 3), 3, 0, 270, &true);
+        graphics.fill_annulus(&mut buffer, Point::new(11, 3), 4, 2, 180, 360, &true);
+        let pie = render(&buffer);
+
+        let expected = [
+            "......###.......",
+            ".....#...#......",
+            "....#.....#.....",
+            "....#.....#.....",
+            "................",
+            ".....#...#......",
+            "......###.......",
+            "................",
+        ];
+        assert_eq!(arc, expected);
+
+        let expected = [
+            "..##....#######.",
+            ".###....#######.",
+            "####...###...###",
+            "##########...###",
+            "#######.........",
+            ".#####..........",
+            "..###...........",
+            "................",
+        ];
+        assert_eq!(pie, expected);
+    }
+
+    #[test]
+    fn test_fill_polygon() {
+        let graphics = Graphics::new(0, 0, W, H);
+
+        // Rectangle traced twice, so the winding number inside is two
+        let points = [
+            Point::new(1, 1),
+            Point::new(6, 1),
+            Point::new(6, 5),
+            Point::new(1, 5),
+            Point::new(1, 1),
+            Point::new(6, 1),
+            Point::new(6, 5),
+            Point::new(1, 5),
+        ];
+        let arrow = [
+            Point::new(8, 2),
+            Point::new(11, 2),
+            Point::new(11, 0),
+            Point::new(15, 3),
+            Point::new(11, 6),
+            Point::new(11, 4),
+            Point::new(8, 4),
+        ];
+
+        let mut even_odd = [0u8; W * H / 8];
+        let mut buffer = bw_buffer(&mut even_odd);
+        graphics.fill_polygon(&mut buffer, &points, FillRule::EvenOdd, &true);
+        graphics.fill_polygon(&mut buffer, &arrow, FillRule::EvenOdd, &true);
+        let even_odd = render(&buffer);
+
+        let mut non_zero = [0u8; W * H / 8];
+        let mut buffer = bw_buffer(&mut non_zero);
+        graphics.fill_polygon(&mut buffer, &points, FillRule::NonZero, &true);
+        graphics.fill_triangle(
+            &mut buffer,
+            Point::new(9, 1),
+            Point::new(20, 3),
+            Point::new(9, 9),
+            &true,
+        );
+        let non_zero = render(&buffer);
+
+        let expected = [
+            "...........#....",
+            ".######....###..",
+            ".#....#.#######.",
+            ".#....#.########",
+            ".#....#.#######.",
+            ".######....##...",
+            "...........#....",
+            "................",
+        ];
+        assert_eq!(even_odd, expected);
+
+        // Triangle extends past the right and bottom edges
+        let expected = [
+            "................",
+            ".######..###....",
+            ".######..#######",
+            ".######..#######",
+            ".######..#######",
+            ".######..#######",
+            ".........#######",
+            ".........#####..",
+        ];
+        assert_eq!(non_zero, expected);
+    }
+
+    #[test]
+    fn test_draw_line_clipped() {
+        const BIG: usize = 64;
+
+        let lines = [
+            (Point::new(2, 1), Point::new(40, 9)),
+            (Point::new(30, 2), Point::new(3, 30)),
+            (Point::new(14, 0), Point::new(17, 50)),
+            (Point::new(0, 7), Point::new(63, 8)),
+            (Point::new(20, 3), Point::new(40, 5)),
+        ];
+
+        for (a, b) in lines.iter() {
+            // Clipped rendering must match the visible section of the unclipped line
+            let mut big = [0u8; BIG * BIG / 8];
+            let mut buffer = Buffer::<PixelBW>::new(BIG, BIG, 0, 0, &mut big);
+            Graphics::new(0, 0, BIG, BIG).draw_line(&mut buffer, *a, *b, &Stroke::default(), &true);
+            let unclipped: Vec<String> =
+                render(&buffer).iter().map(|r| r[..W].to_string()).collect();
+
+            let mut data = [0u8; W * H / 8];
+            let mut buffer = bw_buffer(&mut data);
+            Graphics::new(0, 0, W, H).draw_line(&mut buffer, *a, *b, &Stroke::default(), &true);
+
+            assert_eq!(render(&buffer), unclipped, "line {:?} to {:?}", a, b);
+        }
+
+        // Lines far outside the bounds are rejected without iterating over them
+        let mut data = [0u8; W * H / 8];
+        let mut buffer = bw_buffer(&mut data);
+        let graphics = Graphics::new(0, 0, W, H);
+        graphics.draw_line(
+            &mut buffer,
+            Point::new(-(1 << 40), 9),
+            Point::new(1 << 40, 9),
+            &Stroke::default(),
+            &true,
+        );
+        graphics.draw_line(
+            &mut buffer,
+            Point::new(-(1 << 40), 3),
+            Point::new(1 << 40, 3),
+            &Stroke::default(),
+            &true,
+        );
+
+        let mut expected = vec!["................"; H];
+        expected[3] = "################";
+        assert_eq!(render(&buffer), expected);
+
+        // Long diagonals clip without overflowing the intersection calculation
+        let mut data = [0u8; W * H / 8];
+        let mut buffer = bw_buffer(&mut data);
+        graphics.draw_line(
+            &mut buffer,
+            Point::new(-100_000, -100_000),
+            Point::new(100_000, 100_000),
+            &Stroke::default(),
+            &true,
+        );
+
+        let expected: Vec<String> = (0..H)
+            .map(|y| (0..W).map(|x| if x == y { '#' } else { '.' }).collect())
+            .collect();
+        assert_eq!(render(&buffer), expected);
+    }
+
+    #[test]
+    fn test_draw_rect() {
+        let mut data = [0u8; W * H / 8];
+        let mut buffer = bw_buffer(&mut data);
+        let graphics = Graphics::new(0, 0, W, H);
+
+        graphics.draw_rect(
+            &mut buffer,
+            Rect::new(1, 1, 4, 3),
+            &Stroke::default(),
+            &true,
+        );
+        graphics.draw_rect(
+            &mut buffer,
+            Rect::new(8, 2, 20, 20),
+            &Stroke::default(),
+            &true,
+        );
+
+        let expected = [
+            "................",
+            ".#####..........",
+            ".#...#..########",
+            ".#...#..#.......",
+            ".#####..#.......",
+            "........#.......",
+            "........#.......",
+            "........#.......",
+        ];
+        assert_eq!(render(&buffer), expected);
     }
 
-    //pub fn draw_sprite(&self, b: &mut Buff<Pixel>, p: &point::Point, s: &sprite::Sprite) { }
-    //pub fn draw_text(&self, b: &mut Buff<Pixel>, ) { }
+    #[test]
+    fn test_negative_coordinates() {
+        let mut data = [0u8; W * H / 8];
+        let mut buffer = bw_buffer(&mut data);
+
+        // Graphics context partially off the left and top of the buffer
+        let graphics = Graphics::new(-4, -2, 12, 12);
+
+        graphics.fill_rect(&mut buffer, Rect::new(-10, -10, 16, 14), &true);
+        graphics.fill_circle(&mut buffer, Point::new(14, 5), 3, &true);
+        graphics.draw_text(&mut buffer, Point::new(7, 1), &FONT, "ij", &true);
+
+        let expected = [
+            "##.#.#..........",
+            "##..............",
+            "...#.#.#........",
+            "....#..#........",
+            ".......#........",
+            "................",
+            "................",
+            "................",
+        ];
+        assert_eq!(render(&buffer), expected);
+    }
+
+    #[test]
+    fn test_nest_bounds() {
+        let mut data = [0u8; W * H / 8];
+        let mut buffer = bw_buffer(&mut data);
+        let mut graphics = Graphics::new(2, 1, 8, 6);
+
+        // Child extends past the parent on all sides, but keeps its own origin
+        graphics.nest_bounds(&Rect::new(-3, 2, 20, 20));
+        assert_eq!(graphics.get_bounds(), Rect::new(-1, 3, 20, 20));
+        assert_eq!(graphics.get_clip(), Rect::new(2, 3, 8, 4));
+
+        let bounds = graphics.get_bounds();
+        graphics.fill_rect(&mut buffer, Rect::new(0, 0, bounds.w, bounds.h), &true);
+        graphics.draw_line(
+            &mut buffer,
+            Point::new(0, 0),
+            Point::new(5, 5),
+            &Stroke::default(),
+            &false,
+        );
+
+        let expected = [
+            "................",
+            "................",
+            "................",
+            "..########......",
+            "..########......",
+            "..########......",
+            "...#######......",
+            "................",
+        ];
+        assert_eq!(render(&buffer), expected);
+    }
+
+    #[test]
+    fn test_scroll() {
+        let mut data = [0u8; W * H / 8];
+        let mut buffer = bw_buffer(&mut data);
+        let mut graphics = Graphics::new(0, 0, W, H);
+
+        graphics.draw_line(
+            &mut buffer,
+            Point::new(0, 0),
+            Point::new(15, 7),
+            &Stroke::default(),
+            &true,
+        );
+
+        // Scroll a region extending past the clip area up by two rows
+        graphics.push_clip(&Rect::new(0, 0, 12, 8));
+        graphics.push_translation(Point::new(4, 1));
+        graphics.scroll(&mut buffer, Rect::new(0, 0, 20, 20), 0, -2, &false);
+
+        let expected = [
+            "##..............",
+            "..##..##........",
+            "........##......",
+            "..........##....",
+            "................",
+            "................",
+            "............##..",
+            "..............##",
+        ];
+        assert_eq!(render(&buffer), expected);
+    }
+
+    #[test]
+    fn test_clip_stack() {
+        let mut data = [0u8; W * H / 8];
+        let mut buffer = bw_buffer(&mut data);
+        let mut graphics = Graphics::new(0, 0, W, H);
+
+        // Nested clips are limited to their parents, translations keep the clip area
+        graphics.push_clip(&Rect::new(2, 1, 10, 6));
+        graphics.push_translation(Point::new(4, 2));
+        graphics.push_clip(&Rect::new(-2, -2, 20, 3));
+        assert_eq!(graphics.get_clip(), Rect::new(2, 1, 10, 2));
+
+        graphics.fill_rect(&mut buffer, Rect::new(-8, -8, 32, 32), &true);
+        graphics.pop();
+        graphics.draw_line(
+            &mut buffer,
+            Point::new(0, 0),
+            Point::new(0, 10),
+            &Stroke::default(),
+            &false,
+        );
+
+        graphics.pop();
+        graphics.pop();
+        assert_eq!(graphics.get_clip(), Rect::new(0, 0, W, H));
+        graphics.pop();
+        assert_eq!(graphics.get_bounds(), Rect::new(0, 0, W, H));
+
+        let expected = [
+            "................",
+            "..##########....",
+            "..##.#######....",
+            "................",
+            "................",
+            "................",
+            "................",
+            "................",
+        ];
+        assert_eq!(render(&buffer), expected);
+    }
+
+    #[test]
+    fn test_scope() {
+        let mut graphics = Graphics::<PixelBW>::new(0, 0, W, H);
+
+        {
+            let mut scope = graphics.scope();
+            scope.push_bounds(&Rect::new(4, 4, 4, 4));
+            scope.push_clip(&Rect::new(1, 1, 1, 1));
+            assert_eq!(scope.get_clip(), Rect::new(5, 5, 1, 1));
+        }
+        assert_eq!(graphics.get_bounds(), Rect::new(0, 0, W, H));
+        assert_eq!(graphics.get_clip(), Rect::new(0, 0, W, H));
+
+        // State is restored when unwinding
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let mut scope = graphics.scope();
+            scope.push_translation(Point::new(3, 3));
+            panic!("render failed");
+        }));
+        assert!(result.is_err());
+        assert_eq!(graphics.get_bounds(), Rect::new(0, 0, W, H));
+    }
+
+    #[test]
+    fn test_stroke_styles() {
+        let mut data = [0u8; W * H / 8];
+        let mut buffer = bw_buffer(&mut data);
+        let graphics = Graphics::new(0, 0, W, H);
+
+        let dashed = Stroke::new(1).dashed(&[3, 2]);
+        graphics.draw_line(
+            &mut buffer,
+            Point::new(0, 0),
+            Point::new(15, 0),
+            &dashed,
+            &true,
+        );
+
+        let thick = Stroke::new(3);
+        graphics.draw_line(
+            &mut buffer,
+            Point::new(2, 3),
+            Point::new(6, 3),
+            &thick,
+            &true,
+        );
+
+        let square = Stroke::new(3).cap(Cap::Square);
+        graphics.draw_line(
+            &mut buffer,
+            Point::new(10, 3),
+            Point::new(12, 3),
+            &square,
+            &true,
+        );
+
+        let thick_dashed = Stroke::new(2).dashed(&[2, 2]);
+        graphics.draw_line(
+            &mut buffer,
+            Point::new(0, 6),
+            Point::new(9, 6),
+            &thick_dashed,
+            &true,
+        );
+
+        let expected = [
+            "###..###..###...",
+            "................",
+            "..#####..#####..",
+            "..#####..#####..",
+            "..#####..#####..",
+            "................",
+            "##..##..##......",
+            "##..##..##......",
+        ];
+        assert_eq!(render(&buffer), expected);
+    }
+
+    #[test]
+    fn test_stroke_rect() {
+        let mut styled = [0u8; W * H / 8];
+        let mut buffer = bw_buffer(&mut styled);
+        let graphics = Graphics::new(0, 0, W, H);
+
+        graphics.draw_rect(&mut buffer, Rect::new(1, 1, 10, 5), &Stroke::new(3), &true);
+
+        let expected = [
+            ".###########....",
+            "#############...",
+            "#############...",
+            "###.......###...",
+            "###.......###...",
+            "#############...",
+            "#############...",
+            ".###########....",
+        ];
+        assert_eq!(render(&buffer), expected);
+
+        // Single pixel solid strokes match the unstyled outlines
+        let mut plain = [0u8; W * H / 8];
+        let mut buffer = bw_buffer(&mut plain);
+        graphics.draw_ellipse(
+            &mut buffer,
+            Rect::new(1, 1, 12, 6),
+            &Stroke::default(),
+            &true,
+        );
+        graphics.draw_line(
+            &mut buffer,
+            Point::new(0, 7),
+            Point::new(15, 2),
+            &Stroke::default(),
+            &true,
+        );
+        let plain = render(&buffer);
+
+        let mut styled = [0u8; W * H / 8];
+        let mut buffer = bw_buffer(&mut styled);
+        let stroke = Stroke::default();
+        graphics.draw_ellipse(&mut buffer, Rect::new(1, 1, 12, 6), &stroke, &true);
+        graphics.draw_line(
+            &mut buffer,
+            Point::new(0, 7),
+            Point::new(15, 2),
+            &stroke,
+            &true,
+        );
+
+        assert_eq!(render(&buffer), plain);
+    }
+
+    #[test]
+    fn test_fill_pattern() {
+        let mut data = [0u8; W * H / 8];
+        let mut buffer = bw_buffer(&mut data);
+        let graphics = Graphics::new(0, 0, W, H);
+
+        // Patterns without a background leave clear bits unchanged
+        let checker = Fill::pattern(fill::CHECKER, true, None);
+        graphics.fill_rect(&mut buffer, Rect::new(0, 0, 7, 8), &checker);
+
+        let vertical = Fill::pattern(fill::VERTICAL, true, Some(false));
+        graphics.fill_rect(&mut buffer, Rect::new(9, 1, 5, 6), &true);
+        graphics.fill_circle(&mut buffer, Point::new(11, 3), 3, &vertical);
+
+        let expected = [
+            "#.#.#.#.....#...",
+            ".#.#.#......#...",
+            "#.#.#.#.#...#...",
+            ".#.#.#..#...#...",
+            "#.#.#.#.#...#...",
+            ".#.#.#......#...",
+            "#.#.#.#..#..##..",
+            ".#.#.#..........",
+        ];
+        assert_eq!(render(&buffer), expected);
+    }
 }
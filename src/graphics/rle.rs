@@ -0,0 +1,386 @@
+//! Run-length encoded sprite formats, decoded while drawing without a decompression buffer
+//!
+//! Pixels are encoded in rows from the top left, with runs continuing across rows.
+//!
+//! - black and white sprites use one byte per run, with the pixel value in the most
+//!   significant bit and the run length minus one in the remaining bits
+//! - palette sprites use two bytes per run, a palette index and the run length minus one
+//!
+//! Sprites keep the position of the last run accessed, so drawing in row order decodes
+//! each run once. Accessing an earlier pixel rewinds to the start of the current row, or
+//! to the nearest of a small index of row starts.
+//!
+//! Copyright 2019 Ryan Kurte
+
+use std::fmt;
+
+use crate::types::pixel::PixelBW;
+
+use super::sprite::Sprite;
+
+/// Maximum run length of black and white sprites
+const BW_RUN: usize = 128;
+/// Maximum run length of palette sprites
+const PALETTE_RUN: usize = 256;
+/// Number of evenly spaced row starts indexed for seeking backwards
+const ROW_MARKS: usize = 8;
+
+/// Errors that can occur when parsing a run-length encoded sprite
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Error {
+    /// Runs end before the last pixel of the sprite
+    Truncated,
+    /// Runs extend past the last pixel of the sprite
+    Overrun,
+    /// Palette index is outside the palette
+    InvalidIndex(u8),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Truncated => write!(f, "sprite data truncated"),
+            Error::Overrun => write!(f, "sprite data overruns image"),
+            Error::InvalidIndex(i) => write!(f, "invalid palette index: {}", i),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Position of a run in the encoded data
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Run {
+    /// Offset of the run in the data
+    offset: usize,
+    /// Index of the first pixel in the run
+    start: usize,
+    /// Index of the first pixel after the run, zero if not yet decoded
+    end: usize,
+}
+
+/// Current run in the encoded data, with the runs at row starts to seek back to
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Cursor {
+    /// Run containing the last pixel accessed
+    run: Run,
+    /// Run containing the first pixel of the row last accessed
+    row: Run,
+    /// Runs containing the first pixel of evenly spaced rows
+    marks: [Run; ROW_MARKS],
+    /// Pixels per row
+    width: usize,
+    /// Pixels between indexed rows
+    spacing: usize,
+}
+
+impl Cursor {
+    fn new(width: usize, height: usize) -> Self {
+        let width = width.max(1);
+        Cursor {
+            run: Run::default(),
+            row: Run::default(),
+            marks: [Run::default(); ROW_MARKS],
+            width,
+            spacing: height.div_ceil(ROW_MARKS).max(1) * width,
+        }
+    }
+
+    /// Move to the run containing a pixel index, using `stride` bytes per run and
+    /// `len` to fetch the length of the run at an offset
+    fn seek<F: Fn(usize) -> usize>(&mut self, index: usize, stride: usize, len: F) {
+        let row = index - index % self.width;
+
+        // Rewind to the latest known run at or before the start of the row
+        if index < self.run.start || self.run.end == 0 {
+            let marks = &self.marks[..=(row / self.spacing).min(ROW_MARKS - 1)];
+            self.run = if self.row.end != 0 && self.row.start <= row {
+                self.row
+            } else if let Some(m) = marks.iter().rev().find(|m| m.end != 0) {
+                *m
+            } else {
+                Run {
+                    offset: 0,
+                    start: 0,
+                    end: len(0),
+                }
+            };
+        }
+
+        loop {
+            let Run { start, end, .. } = self.run;
+            if start <= row && row < end {
+                self.row = self.run;
+            }
+            for m in start.div_ceil(self.spacing)..ROW_MARKS {
+                if m * self.spacing >= end {
+                    break;
+                }
+                self.marks[m] = self.run;
+            }
+
+            if index < end {
+                break;
+            }
+            self.run.offset += stride;
+            self.run.start = end;
+            self.run.end = end + len(self.run.offset);
+        }
+    }
+}
+
+/// Check that runs exactly cover the pixels of a sprite
+fn validate<F: Fn(usize) -> usize>(
+    data: &[u8],
+    stride: usize,
+    pixels: usize,
+    len: F,
+) -> Result<(), Error> {
+    if !data.chunks_exact(stride).remainder().is_empty() {
+        return Err(Error::Truncated);
+    }
+
+    let total: usize = (0..data.len()).step_by(stride).map(len).sum();
+    match total {
+        t if t < pixels => Err(Error::Truncated),
+        t if t > pixels => Err(Error::Overrun),
+        _ => Ok(()),
+    }
+}
+
+/// Run-length encoded black and white sprite
+#[derive(Clone, Debug, PartialEq)]
+pub struct RleBW<'a> {
+    width: usize,
+    height: usize,
+    data: &'a [u8],
+    cursor: Cursor,
+}
+
+impl<'a> RleBW<'a> {
+    /// Parse and validate a run-length encoded black and white sprite
+    pub fn parse(width: usize, height: usize, data: &'a [u8]) -> Result<Self, Error> {
+        validate(data, 1, width * height, |i| Self::run(data, i))?;
+
+        Ok(RleBW {
+            width,
+            height,
+            data,
+            cursor: Cursor::new(width, height),
+        })
+    }
+
+    fn run(data: &[u8], offset: usize) -> usize {
+        (data[offset] & 0x7f) as usize + 1
+    }
+}
+
+impl<'a> Sprite<PixelBW> for RleBW<'a> {
+    fn get(&mut self, x: usize, y: usize) -> &PixelBW {
+        let data = self.data;
+        self.cursor
+            .seek(y * self.width + x, 1, |i| Self::run(data, i));
+
+        if data[self.cursor.run.offset] & 0x80 != 0 {
+            &true
+        } else {
+            &false
+        }
+    }
+
+    fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+}
+
+/// Run-length encoded sprite with colours from a palette of up to 256 entries
+#[derive(Clone, Debug, PartialEq)]
+pub struct RlePalette<'a, Pixel> {
+    width: usize,
+    height: usize,
+    palette: &'a [Pixel],
+    data: &'a [u8],
+    cursor: Cursor,
+}
+
+impl<'a, Pixel> RlePalette<'a, Pixel> {
+    /// Parse and validate a run-length encoded palette sprite
+    pub fn parse(
+        width: usize,
+        height: usize,
+        palette: &'a [Pixel],
+        data: &'a [u8],
+    ) -> Result<Self, Error> {
+        validate(data, 2, width * height, |i| Self::run(data, i))?;
+
+        // Check indices so lookups can not fail later
+        if let Some(i) = data
+            .iter()
+            .step_by(2)
+            .find(|&&i| i as usize >= palette.len())
+        {
+            return Err(Error::InvalidIndex(*i));
+        }
+
+        Ok(RlePalette {
+            width,
+            height,
+            palette,
+            data,
+            cursor: Cursor::new(width, height),
+        })
+    }
+
+    fn run(data: &[u8], offset: usize) -> usize {
+        data[offset + 1] as usize + 1
+    }
+}
+
+impl<'a, Pixel> Sprite<Pixel> for RlePalette<'a, Pixel> {
+    fn get(&mut self, x: usize, y: usize) -> &Pixel {
+        let data = self.data;
+        self.cursor
+            .seek(y * self.width + x, 2, |i| Self::run(data, i));
+
+        &self.palette[data[self.cursor.run.offset] as usize]
+    }
+
+    fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+}
+
+/// Split values into runs of equal values up to a maximum length
+fn runs<T: PartialEq>(values: &[T], max: usize) -> impl Iterator<Item = (&T, usize)> + '_ {
+    let mut i = 0;
+
+    std::iter::from_fn(move || {
+        let v = values.get(i)?;
+        let len = values[i..].iter().take(max).take_while(|&w| w == v).count();
+
+        i += len;
+        Some((v, len))
+    })
+}
+
+/// Encode black and white pixels (in rows from the top left) as a run-length encoded sprite
+pub fn encode_bw(pixels: &[PixelBW]) -> Vec<u8> {
+    runs(pixels, BW_RUN)
+        .map(|(&p, len)| (p as u8) << 7 | (len - 1) as u8)
+        .collect()
+}
+
+/// Encode palette indices (in rows from the top left) as a run-length encoded palette sprite
+pub fn encode_palette(indices: &[u8]) -> Vec<u8> {
+    runs(indices, PALETTE_RUN)
+        .flat_map(|(&i, len)| [i, (len - 1) as u8])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::core::buffer::{Buff, Buffer};
+    use crate::graphics::sprite::Bitmap;
+    use crate::graphics::test_utils::{H, W};
+    use crate::graphics::Graphics;
+    use crate::types::pixel::PixelRGB24;
+    use crate::types::point::Point;
+    use crate::types::rect::Rect;
+
+    #[test]
+    fn test_rle_bw() {
+        // Long runs are split at the maximum run length
+        let mut pixels = vec![false; 200];
+        pixels[3..6].iter_mut().for_each(|p| *p = true);
+        pixels.extend_from_slice(&[true; 56]);
+
+        let data = encode_bw(&pixels);
+        assert_eq!(data, vec![0x02, 0x82, 0x7f, 0x41, 0xb7]);
+
+        let mut sprite = RleBW::parse(W, W, &data).unwrap();
+        let mut bitmap = Bitmap::new(W, W, &pixels);
+        for y in 0..W {
+            for x in 0..W {
+                assert_eq!(sprite.get(x, y), bitmap.get(x, y), "({}, {})", x, y);
+            }
+        }
+
+        // Earlier pixels rewind to the start of the row or an indexed row
+        assert_eq!(sprite.get(4, 0), &true);
+        assert_eq!(sprite.get(0, 13), &true);
+        assert_eq!(sprite.get(8, 3), &false);
+
+        assert_eq!(RleBW::parse(W, W + 1, &data), Err(Error::Truncated));
+        assert_eq!(RleBW::parse(W, W - 1, &data), Err(Error::Overrun));
+    }
+
+    #[test]
+    fn test_rle_palette() {
+        let palette = [
+            PixelRGB24::new(0, 0, 0),
+            PixelRGB24::new(255, 0, 0),
+            PixelRGB24::new(0, 0, 255),
+        ];
+        let indices: Vec<u8> = (0..W * H).map(|i| (i % 7 / 3) as u8).collect();
+
+        let data = encode_palette(&indices);
+        assert_eq!(&data[..6], &[0, 2, 1, 2, 2, 0]);
+
+        let mut sprite = RlePalette::parse(W, H, &palette, &data).unwrap();
+
+        // Drawing a clipped sprite decodes in place
+        let mut a = [0u8; W * H * 3];
+        let mut buffer = Buffer::<PixelRGB24>::new(W, H, 0, 0, &mut a);
+        let mut graphics = Graphics::new(0, 0, W, H);
+        graphics.set_clip(&Rect::new(2, 1, 12, 6));
+        graphics.draw_sprite(&mut buffer, Point::new(0, 0), &mut sprite);
+
+        for y in 0..H {
+            for x in 0..W {
+                let expected = match (x, y) {
+                    (2..=13, 1..=6) => palette[indices[y * W + x] as usize],
+                    _ => PixelRGB24::new(0, 0, 0),
+                };
+                assert_eq!(buffer.get(x, y), expected, "({}, {})", x, y);
+            }
+        }
+
+        assert_eq!(
+            RlePalette::parse(W, H, &palette[..2], &data),
+            Err(Error::InvalidIndex(2))
+        );
+        assert_eq!(
+            RlePalette::parse(W, H, &palette, &data[..data.len() - 1]),
+            Err(Error::Truncated)
+        );
+    }
+
+    #[test]
+    fn test_seek_runs() {
+        // Single pixel runs, counting runs decoded while drawing scaled up by two
+        let decoded = std::cell::Cell::new(0);
+        let len = |_| {
+            decoded.set(decoded.get() + 1);
+            1
+        };
+
+        let mut cursor = Cursor::new(W, H);
+        for y in 0..H * 2 {
+            for x in 0..W * 2 {
+                cursor.seek(y / 2 * W + x / 2, 1, len);
+                assert_eq!(cursor.run.start, y / 2 * W + x / 2);
+            }
+        }
+
+        // Each row is decoded twice, rewinding to the start of the row
+        assert_eq!(decoded.get(), W * H * 2 - H);
+
+        // Rows before the current one rewind to the nearest indexed row
+        decoded.set(0);
+        cursor.seek(W * 3 + 2, 1, len);
+        assert_eq!(cursor.run.start, W * 3 + 2);
+        assert_eq!(decoded.get(), 2);
+    }
+}
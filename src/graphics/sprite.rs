@@ -0,0 +1,342 @@
+//! Sprites and sprite drawing functions, including scaled and rotated blits
+//!
+//! Scaled and rotated sprites are sampled at fixed point positions, either from the nearest
+//! pixel or by bilinear interpolation for colours that support partial coverage.
+//!
+//! Copyright 2019 Ryan Kurte
+
+use crate::core::buffer::Buff;
+use crate::types::pixel::{Blend, PixelBW};
+use crate::types::point::Point;
+use crate::types::rect::Rect;
+
+use super::trig::{self, isqrt};
+use super::Graphics;
+
+/// Fractional bits of fixed point sample positions
+const SHIFT: u32 = 8;
+const ONE: isize = 1 << SHIFT;
+
+/// Sprite trait implemented by types that can be rendered from a buffer
+pub trait Sprite<Pixel> {
+    fn get(&mut self, x: usize, y: usize) -> &Pixel;
+    fn size(&self) -> (usize, usize);
+}
+
+/// Uncompressed sprite with one pixel per element, stored in rows from the top left
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bitmap<'a, Pixel> {
+    width: usize,
+    height: usize,
+    data: &'a [Pixel],
+}
+
+impl<'a, Pixel> Bitmap<'a, Pixel> {
+    /// Create a bitmap sprite over pixel data, which must contain width * height pixels
+    pub fn new(width: usize, height: usize, data: &'a [Pixel]) -> Self {
+        assert_eq!(data.len(), width * height, "bitmap data size mismatch");
+
+        Bitmap {
+            width,
+            height,
+            data,
+        }
+    }
+}
+
+impl<'a, Pixel> Sprite<Pixel> for Bitmap<'a, Pixel> {
+    fn get(&mut self, x: usize, y: usize) -> &Pixel {
+        &self.data[y * self.width + x]
+    }
+
+    fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+}
+
+/// Sampling filter for scaled and rotated sprites
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Filter {
+    /// Use the nearest sprite pixel
+    Nearest,
+    /// Interpolate between the four nearest sprite pixels, falling back to the nearest
+    /// pixel for colours that can't display partial coverage
+    Bilinear,
+}
+
+/// Sample a sprite at a fixed point position, returning nothing outside the sprite
+fn sample<Pixel: Blend + Clone>(
+    s: &mut dyn Sprite<Pixel>,
+    u: isize,
+    v: isize,
+    filter: Filter,
+) -> Option<Pixel> {
+    let (w, h) = s.size();
+    let (w, h) = (w as isize, h as isize);
+
+    let (x, y) = (u >> SHIFT, v >> SHIFT);
+    if x < 0 || y < 0 || x >= w || y >= h {
+        return None;
+    }
+
+    if filter == Filter::Nearest || !Pixel::smooth() {
+        return Some(s.get(x as usize, y as usize).clone());
+    }
+
+    // Interpolate between pixel centres, clamping neighbours to the sprite edges
+    let (u, v) = (u - ONE / 2, v - ONE / 2);
+    let (x, y) = (u >> SHIFT, v >> SHIFT);
+    let (fx, fy) = ((u & (ONE - 1)) as u8, (v & (ONE - 1)) as u8);
+
+    let mut get = |x: isize, y: isize| {
+        s.get(x.clamp(0, w - 1) as usize, y.clamp(0, h - 1) as usize)
+            .clone()
+    };
+
+    let top = get(x, y).blend(&get(x + 1, y), fx);
+    let bottom = get(x, y + 1).blend(&get(x + 1, y + 1), fx);
+
+    Some(top.blend(&bottom, fy))
+}
+
+impl<Pixel> Graphics<Pixel> {
+    /// Draws a sprite with its top left corner at the provided position
+    pub fn draw_sprite(&self, buf: &mut dyn Buff<Pixel>, pos: Point, s: &mut dyn Sprite<Pixel>) {
+        let (w, h) = s.size();
+        let r = match Rect::new(pos.x, pos.y, w, h).intersect(&self.local_clip()) {
+            Some(r) => r,
+            None => return,
+        };
+
+        for y in r.y..r.bottom() {
+            for x in r.x..r.right() {
+                let p = s.get((x - pos.x) as usize, (y - pos.y) as usize);
+                self.set(buf, x, y, p);
+            }
+        }
+    }
+
+    /// Draws a sprite with its top left corner at the provided position, skipping
+    /// pixels that are not set in a transparency mask of the same size
+    pub fn draw_sprite_masked(
+        &self,
+        buf: &mut dyn Buff<Pixel>,
+        pos: Point,
+        s: &mut dyn Sprite<Pixel>,
+        mask: &mut dyn Sprite<PixelBW>,
+    ) {
+        assert_eq!(s.size(), mask.size(), "sprite and mask size mismatch");
+
+        let (w, h) = s.size();
+        let r = match Rect::new(pos.x, pos.y, w, h).intersect(&self.local_clip()) {
+            Some(r) => r,
+            None => return,
+        };
+
+        for y in r.y..r.bottom() {
+            for x in r.x..r.right() {
+                let (sx, sy) = ((x - pos.x) as usize, (y - pos.y) as usize);
+                if *mask.get(sx, sy) {
+                    self.set(buf, x, y, s.get(sx, sy));
+                }
+            }
+        }
+    }
+}
+
+impl<Pixel: Blend + Clone> Graphics<Pixel> {
+    /// Draws a sprite scaled to fill the provided rectangle
+    pub fn draw_sprite_scaled(
+        &self,
+        buf: &mut dyn Buff<Pixel>,
+        r: Rect,
+        s: &mut dyn Sprite<Pixel>,
+        filter: Filter,
+    ) {
+        let (w, h) = s.size();
+        let visible = match r.intersect(&self.local_clip()) {
+            Some(v) => v,
+            None => return,
+        };
+
+        // Map destination pixel centres to positions in the sprite
+        let scale = |d: isize, from: usize, to: usize| {
+            ((d * 2 + 1) * from as isize * ONE) / (2 * to as isize)
+        };
+
+        for y in visible.y..visible.bottom() {
+            let v = scale(y - r.y, h, r.h);
+            for x in visible.x..visible.right() {
+                let u = scale(x - r.x, w, r.w);
+                if let Some(p) = sample(s, u, v, filter) {
+                    self.set(buf, x, y, &p);
+                }
+            }
+        }
+    }
+
+    /// Draws a sprite rotated clockwise by an angle (in degrees) around a pivot point in
+    /// the sprite, with the pivot drawn at the provided position
+    pub fn draw_sprite_rotated(
+        &self,
+        buf: &mut dyn Buff<Pixel>,
+        pos: Point,
+        pivot: Point,
+        angle: isize,
+        s: &mut dyn Sprite<Pixel>,
+        filter: Filter,
+    ) {
+        let (w, h) = s.size();
+        let (w, h) = (w as isize, h as isize);
+
+        // Bound the rotated sprite by the furthest corner from the pivot
+        let (dx, dy) = (pivot.x.max(w - pivot.x), pivot.y.max(h - pivot.y));
+        let radius = isqrt(dx * dx + dy * dy) + 1;
+        let extent = Rect::new(
+            pos.x - radius,
+            pos.y - radius,
+            radius as usize * 2 + 1,
+            radius as usize * 2 + 1,
+        );
+        let visible = match extent.intersect(&self.local_clip()) {
+            Some(v) => v,
+            None => return,
+        };
+
+        // Rotate each destination pixel back into the sprite, relative to the pivot centre
+        let (sin, cos) = (trig::sin(angle), trig::cos(angle));
+        let scale = trig::ONE / ONE;
+        let centre = (pivot.x * ONE + ONE / 2, pivot.y * ONE + ONE / 2);
+
+        for y in visible.y..visible.bottom() {
+            for x in visible.x..visible.right() {
+                let (rx, ry) = (x - pos.x, y - pos.y);
+                let u = centre.0 + (rx * cos + ry * sin) / scale;
+                let v = centre.1 + (ry * cos - rx * sin) / scale;
+
+                if let Some(p) = sample(s, u, v, filter) {
+                    self.set(buf, x, y, &p);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::core::buffer::Buffer;
+    use crate::graphics::test_utils::{bw_buffer, render, H, W};
+    use crate::types::pixel::PixelG8;
+
+    /// Arrow pointing right, with the pivot at (1, 1)
+    const ARROW: [bool; 12] = [
+        false, false, true, false, //
+        true, true, true, true, //
+        false, false, true, false, //
+    ];
+
+    #[test]
+    fn test_draw_sprite() {
+        let mut data = [0u8; W * H / 8];
+        let mut buffer = bw_buffer(&mut data);
+        let mut graphics = Graphics::new(0, 0, W, H);
+        let mut arrow = Bitmap::new(4, 3, &ARROW);
+
+        graphics.set_clip(&Rect::new(0, 0, 14, 8));
+        graphics.draw_sprite(&mut buffer, Point::new(-1, 0), &mut arrow);
+        graphics.draw_sprite_scaled(
+            &mut buffer,
+            Rect::new(4, 0, 8, 6),
+            &mut arrow,
+            Filter::Nearest,
+        );
+        graphics.draw_sprite(&mut buffer, Point::new(12, 4), &mut arrow);
+
+        // Masked sprites only replace pixels set in the mask
+        let clear = [false; 12];
+        graphics.fill_rect(&mut buffer, Rect::new(0, 5, 6, 3), &true);
+        graphics.draw_sprite_masked(
+            &mut buffer,
+            Point::new(1, 5),
+            &mut Bitmap::new(4, 3, &clear),
+            &mut arrow,
+        );
+
+        let expected = [
+            ".#......##......",
+            "###.....##......",
+            ".#..########....",
+            "....########....",
+            "........##......",
+            "###.##..##..##..",
+            "#....#..........",
+            "###.##..........",
+        ];
+        assert_eq!(render(&buffer), expected);
+    }
+
+    #[test]
+    fn test_draw_sprite_rotated() {
+        let mut data = [0u8; W * H / 8];
+        let mut buffer = bw_buffer(&mut data);
+        let graphics = Graphics::new(0, 0, W, H);
+        let mut arrow = Bitmap::new(4, 3, &ARROW);
+
+        for (i, &angle) in [0, 90, 180, 270].iter().enumerate() {
+            let pos = Point::new(2 + i as isize * 4, 3);
+            let pivot = Point::new(1, 1);
+            graphics.draw_sprite_rotated(
+                &mut buffer,
+                pos,
+                pivot,
+                angle,
+                &mut arrow,
+                Filter::Nearest,
+            );
+        }
+
+        let expected = [
+            "................",
+            "..............#.",
+            "...#..#..#...###",
+            ".####.#.####..#.",
+            "...#.###.#....#.",
+            "......#.........",
+            "................",
+            "................",
+        ];
+        assert_eq!(render(&buffer), expected);
+    }
+
+    #[test]
+    fn test_bilinear() {
+        let gradient = [PixelG8(0), PixelG8(255), PixelG8(255), PixelG8(0)];
+        let mut sprite = Bitmap::new(2, 2, &gradient);
+
+        let mut data = [0u8; 4 * 4];
+        let mut buffer = Buffer::<PixelG8>::new(4, 4, 0, 0, &mut data);
+        let graphics = Graphics::new(0, 0, 4, 4);
+
+        graphics.draw_sprite_scaled(
+            &mut buffer,
+            Rect::new(0, 0, 4, 4),
+            &mut sprite,
+            Filter::Bilinear,
+        );
+        let rows: Vec<Vec<u8>> = (0..4)
+            .map(|y| (0..4).map(|x| buffer.get(x, y).0).collect())
+            .collect();
+
+        assert_eq!(
+            rows,
+            vec![
+                vec![0, 64, 192, 255],
+                vec![64, 96, 160, 191],
+                vec![192, 160, 95, 63],
+                vec![255, 191, 63, 0],
+            ]
+        );
+    }
+}
@@ -0,0 +1,141 @@
+//! Stroke styles for outlined shapes, with line width, dash patterns and cap styles
+//!
+//! Copyright 2019 Ryan Kurte
+
+/// Style used for the ends of stroked lines and dashes
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Cap {
+    /// Lines end at their end points
+    Butt,
+    /// Lines are extended past their end points by half the stroke width
+    Square,
+    /// Lines end in a semicircle, with round joins between segments
+    Round,
+}
+
+/// Stroke style for outlined shapes
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stroke<'a> {
+    /// Line width in pixels
+    pub width: usize,
+    /// Alternating dash and gap lengths in pixels, or empty for a solid line
+    pub dash: &'a [usize],
+    /// Style for line and dash ends
+    pub cap: Cap,
+}
+
+impl<'a> Stroke<'a> {
+    /// Create a solid stroke with the provided width
+    pub fn new(width: usize) -> Self {
+        Stroke {
+            width,
+            dash: &[],
+            cap: Cap::Butt,
+        }
+    }
+
+    /// Set the dash pattern for a stroke
+    pub fn dashed(self, dash: &'a [usize]) -> Self {
+        Stroke { dash, ..self }
+    }
+
+    /// Set the cap style for a stroke
+    pub fn cap(self, cap: Cap) -> Self {
+        Stroke { cap, ..self }
+    }
+
+    /// Check whether a stroke is a solid single pixel line, drawn without building polygons
+    pub(crate) fn is_hairline(&self) -> bool {
+        self.width == 1 && self.dash.is_empty()
+    }
+}
+
+impl<'a> Default for Stroke<'a> {
+    fn default() -> Self {
+        Stroke::new(1)
+    }
+}
+
+/// Dasher tracks the position within a dash pattern along a path
+pub(crate) struct Dasher<'a> {
+    pattern: &'a [usize],
+    index: usize,
+    remaining: usize,
+    on: bool,
+}
+
+impl<'a> Dasher<'a> {
+    pub fn new(pattern: &'a [usize]) -> Self {
+        // Patterns without any length are drawn solid
+        let pattern = if pattern.iter().sum::<usize>() == 0 {
+            &[]
+        } else {
+            pattern
+        };
+
+        Dasher {
+            pattern,
+            index: 0,
+            remaining: pattern.first().cloned().unwrap_or(0),
+            on: true,
+        }
+    }
+
+    /// Split the next section of a path into runs, calling `f` with the start and end
+    /// offsets of each run and whether the run is drawn
+    pub fn runs<F: FnMut(usize, usize, bool)>(&mut self, len: usize, mut f: F) {
+        if self.pattern.is_empty() {
+            f(0, len, true);
+            return;
+        }
+
+        let mut pos = 0;
+        while pos < len {
+            if self.remaining == 0 {
+                self.index = (self.index + 1) % self.pattern.len();
+                self.remaining = self.pattern[self.index];
+                self.on = !self.on;
+                continue;
+            }
+
+            let step = self.remaining.min(len - pos);
+            f(pos, pos + step, self.on);
+
+            pos += step;
+            self.remaining -= step;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn runs(d: &mut Dasher, len: usize) -> Vec<(usize, usize, bool)> {
+        let mut r = vec![];
+        d.runs(len, |s, e, on| r.push((s, e, on)));
+        r
+    }
+
+    #[test]
+    fn test_dasher() {
+        let mut d = Dasher::new(&[3, 2]);
+        assert_eq!(
+            runs(&mut d, 7),
+            vec![(0, 3, true), (3, 5, false), (5, 7, true)]
+        );
+
+        // Pattern continues across path sections
+        assert_eq!(runs(&mut d, 3), vec![(0, 1, true), (1, 3, false)]);
+
+        // Odd length patterns alternate on and off over repeats
+        let mut d = Dasher::new(&[2]);
+        assert_eq!(
+            runs(&mut d, 6),
+            vec![(0, 2, true), (2, 4, false), (4, 6, true)]
+        );
+
+        let mut d = Dasher::new(&[0, 0]);
+        assert_eq!(runs(&mut d, 5), vec![(0, 5, true)]);
+    }
+}
@@ -0,0 +1,27 @@
+//! Shared test fixtures, drawing to small black and white buffers rendered as strings
+//!
+//! Copyright 2019 Ryan Kurte
+
+use crate::core::buffer::{Buff, Buffer};
+use crate::types::pixel::PixelBW;
+
+/// Test buffer width
+pub(crate) const W: usize = 16;
+/// Test buffer height
+pub(crate) const H: usize = 8;
+
+/// Create a black and white test buffer over the provided data (of `W * H / 8` bytes)
+pub(crate) fn bw_buffer(data: &mut [u8]) -> Buffer<'_, PixelBW> {
+    Buffer::<PixelBW>::new(W, H, 0, 0, data)
+}
+
+/// Render a BW buffer as a string for comparison
+pub(crate) fn render(buffer: &Buffer<PixelBW>) -> Vec<String> {
+    (0..H)
+        .map(|y| {
+            (0..W)
+                .map(|x| if buffer.get(x, y) { '#' } else { '.' })
+                .collect()
+        })
+        .collect()
+}
@@ -0,0 +1,143 @@
+//! Integer trigonometry for drawing arcs without floating point support
+//!
+//! Angles are in whole degrees, measured clockwise (in screen coordinates) from the
+//! positive x axis.
+//!
+//! Copyright 2019 Ryan Kurte
+
+/// Fixed point scale for sine and cosine values
+pub const ONE: isize = 4096;
+
+/// Sine of 0 to 90 degrees, scaled by `ONE`
+const SIN_TABLE: [i16; 91] = [
+    0, 71, 143, 214, 286, 357, 428, 499, 570, 641, 711, 782, 852, 921, 991, 1060, 1129, 1198, 1266,
+    1334, 1401, 1468, 1534, 1600, 1666, 1731, 1796, 1860, 1923, 1986, 2048, 2110, 2171, 2231, 2290,
+    2349, 2408, 2465, 2522, 2578, 2633, 2687, 2741, 2793, 2845, 2896, 2946, 2996, 3044, 3091, 3138,
+    3183, 3228, 3271, 3314, 3355, 3396, 3435, 3474, 3511, 3547, 3582, 3617, 3650, 3681, 3712, 3742,
+    3770, 3798, 3824, 3849, 3873, 3896, 3917, 3937, 3956, 3974, 3991, 4006, 4021, 4034, 4046, 4056,
+    4065, 4074, 4080, 4086, 4090, 4094, 4095, 4096,
+];
+
+/// Sine of an angle in degrees, scaled by `ONE`
+pub fn sin(deg: isize) -> isize {
+    let deg = deg.rem_euclid(360) as usize;
+
+    match deg {
+        0..=90 => SIN_TABLE[deg] as isize,
+        91..=180 => SIN_TABLE[180 - deg] as isize,
+        181..=270 => -(SIN_TABLE[deg - 180] as isize),
+        _ => -(SIN_TABLE[360 - deg] as isize),
+    }
+}
+
+/// Cosine of an angle in degrees, scaled by `ONE`
+pub fn cos(deg: isize) -> isize {
+    sin(deg + 90)
+}
+
+/// Integer square root, rounded down
+pub fn isqrt(n: isize) -> isize {
+    if n <= 0 {
+        return 0;
+    }
+
+    // Newton's method, starting from an overestimate
+    let mut x = n;
+    let mut y = (x + 1) / 2;
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+
+    x
+}
+
+/// Angular sector, from a start angle clockwise to an end angle
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sector {
+    start: (isize, isize),
+    end: (isize, isize),
+    sweep: isize,
+}
+
+impl Sector {
+    /// Create a sector between two angles, sweeping a full circle when they differ by a
+    /// multiple of 360 degrees
+    pub fn new(start: isize, end: isize) -> Self {
+        let sweep = match (end - start).rem_euclid(360) {
+            0 if end != start => 360,
+            s => s,
+        };
+
+        Sector {
+            start: (cos(start), sin(start)),
+            end: (cos(end), sin(end)),
+            sweep,
+        }
+    }
+
+    /// Check whether the direction (dx, dy) from the centre lies within the sector
+    pub fn contains(&self, dx: isize, dy: isize) -> bool {
+        let cross = |a: (isize, isize), b: (isize, isize)| a.0 * b.1 - a.1 * b.0;
+        let v = (dx, dy);
+
+        if self.sweep >= 360 {
+            true
+        } else if self.sweep == 0 {
+            // Empty, rather than every direction collinear with the start angle
+            false
+        } else if self.sweep <= 180 {
+            cross(self.start, v) >= 0 && cross(v, self.end) >= 0
+        } else {
+            // Outside the (convex) complementary sector
+            !(cross(self.end, v) > 0 && cross(v, self.start) > 0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sin_cos() {
+        assert_eq!(sin(0), 0);
+        assert_eq!(sin(90), ONE);
+        assert_eq!(sin(210), -ONE / 2);
+        assert_eq!(sin(-90), -ONE);
+        assert_eq!(cos(60), ONE / 2);
+        assert_eq!(cos(180), -ONE);
+        assert_eq!(cos(720), ONE);
+    }
+
+    #[test]
+    fn test_isqrt() {
+        let roots: Vec<_> = (0..10).map(isqrt).collect();
+        assert_eq!(roots, vec![0, 1, 1, 1, 2, 2, 2, 2, 2, 3]);
+        assert_eq!(isqrt(1 << 40), 1 << 20);
+        assert_eq!(isqrt((1 << 40) - 1), (1 << 20) - 1);
+        assert_eq!(isqrt(-4), 0);
+    }
+
+    #[test]
+    fn test_sector() {
+        // Bottom right quadrant (clockwise on screen from 3 to 6 o'clock)
+        let s = Sector::new(0, 90);
+        assert!(s.contains(1, 1));
+        assert!(s.contains(1, 0));
+        assert!(!s.contains(-1, 1));
+        assert!(!s.contains(1, -1));
+
+        // Everything but the bottom right quadrant
+        let s = Sector::new(90, 360);
+        assert!(!s.contains(1, 1));
+        assert!(s.contains(-1, 1));
+        assert!(s.contains(-1, -1));
+        assert!(s.contains(1, -1));
+
+        assert!(Sector::new(45, 405).contains(-3, 2));
+        assert!(!Sector::new(45, 45).contains(-3, 2));
+        assert!(!Sector::new(45, 45).contains(1, 1));
+        assert!(!Sector::new(45, 45).contains(-1, -1));
+    }
+}
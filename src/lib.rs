@@ -8,6 +8,8 @@ pub mod gui;
 
 pub mod graphics;
 
+pub mod fonts;
+
 pub mod widgets;
 
 #[cfg(feature = "sdl")]
@@ -1,5 +1,6 @@
 pub use crate::core::buffer::{Buff, Buffer};
 pub use crate::core::layer::Layer;
 pub use crate::core::window::{OnEvent, OnLoad, OnUnload, Window};
+pub use crate::fonts::{Char, Font, Glyphs};
 pub use crate::gui::Gui;
 pub use crate::types::pixel;
@@ -11,4 +11,5 @@ pub enum Event {
     Select,
     Back,
     Click { x: usize, y: usize },
+    Tick { ms: usize },
 }
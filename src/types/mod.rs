@@ -3,10 +3,12 @@
 //! Copyright 2019 Ryan Kurte
 
 pub mod pixel;
-pub use self::pixel::{PixelBW, PixelRGB24};
+pub use self::pixel::{PixelBW, PixelG8, PixelRGB24};
 pub mod point;
 pub use self::point::Point;
 pub mod rect;
 pub use self::rect::Rect;
+pub mod size;
+pub use self::size::Size;
 pub mod events;
 pub use self::events::Event;
@@ -17,6 +17,24 @@ pub trait RGB {
     fn blue() -> Self;
 }
 
+/// Blend trait implemented by colours that can be mixed for anti-aliased rendering
+pub trait Blend {
+    /// Mix a colour over this one with the provided coverage (0 for none, 255 for full)
+    fn blend(&self, over: &Self, alpha: u8) -> Self;
+
+    /// Whether partial coverage can be displayed, anti-aliased rendering falls back to
+    /// aliased output for colours without this
+    fn smooth() -> bool {
+        true
+    }
+}
+
+/// Mix two colour channels with the provided coverage
+fn mix(under: u8, over: u8, alpha: u8) -> u8 {
+    let a = alpha as u32;
+    ((under as u32 * (255 - a) + over as u32 * a + 127) / 255) as u8
+}
+
 /// 24-bit RGB pixel implementation
 #[derive(Clone, Copy, Default, Debug, PartialEq)]
 pub struct PixelRGB24 {
@@ -81,9 +99,19 @@ impl RGB for PixelRGB24 {
     }
 }
 
+impl Blend for PixelRGB24 {
+    fn blend(&self, over: &Self, alpha: u8) -> Self {
+        Self {
+            r: mix(self.r, over.r, alpha),
+            g: mix(self.g, over.g, alpha),
+            b: mix(self.b, over.b, alpha),
+        }
+    }
+}
+
 /// 8-bit grey-scale pixel implementation
 #[derive(Clone, Copy, Default, Debug, PartialEq)]
-pub struct PixelG8(u8);
+pub struct PixelG8(pub u8);
 
 impl BW for PixelG8 {
     fn black() -> Self {
@@ -106,6 +134,12 @@ impl RGB for PixelG8 {
     }
 }
 
+impl Blend for PixelG8 {
+    fn blend(&self, over: &Self, alpha: u8) -> Self {
+        Self(mix(self.0, over.0, alpha))
+    }
+}
+
 /// 1-bit wlack and white pixel implementation
 pub type PixelBW = bool;
 
@@ -129,3 +163,17 @@ impl RGB for PixelBW {
         true
     }
 }
+
+impl Blend for PixelBW {
+    fn blend(&self, over: &Self, alpha: u8) -> Self {
+        if alpha >= 128 {
+            *over
+        } else {
+            *self
+        }
+    }
+
+    fn smooth() -> bool {
+        false
+    }
+}
@@ -2,15 +2,104 @@
 //!
 //! Copyright 2019 Ryan Kurte
 
-/// Point in 2d space
+use std::convert::TryFrom;
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+
+/// Point in 2d space, using signed coordinates so points may lie off the top or left edges
 #[derive(Clone, Copy, Default, Debug, PartialEq)]
 pub struct Point {
-    pub x: usize,
-    pub y: usize,
+    pub x: isize,
+    pub y: isize,
 }
 
 impl Point {
-    pub fn new(x: usize, y: usize) -> Point {
+    pub fn new(x: isize, y: isize) -> Point {
         Point { x, y }
     }
+
+    /// Create a point from unsigned coordinates
+    pub fn from_unsigned(x: usize, y: usize) -> Point {
+        Point {
+            x: x as isize,
+            y: y as isize,
+        }
+    }
+
+    /// Convert a point to unsigned coordinates, returning None if either coordinate is negative
+    pub fn to_unsigned(&self) -> Option<(usize, usize)> {
+        match (usize::try_from(self.x), usize::try_from(self.y)) {
+            (Ok(x), Ok(y)) => Some((x, y)),
+            _ => None,
+        }
+    }
+}
+
+impl From<(isize, isize)> for Point {
+    fn from(p: (isize, isize)) -> Point {
+        Point { x: p.0, y: p.1 }
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, o: Point) -> Point {
+        Point::new(self.x + o.x, self.y + o.y)
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+
+    fn sub(self, o: Point) -> Point {
+        Point::new(self.x - o.x, self.y - o.y)
+    }
+}
+
+impl Neg for Point {
+    type Output = Point;
+
+    fn neg(self) -> Point {
+        Point::new(-self.x, -self.y)
+    }
+}
+
+impl AddAssign for Point {
+    fn add_assign(&mut self, o: Point) {
+        self.x += o.x;
+        self.y += o.y;
+    }
+}
+
+impl SubAssign for Point {
+    fn sub_assign(&mut self, o: Point) {
+        self.x -= o.x;
+        self.y -= o.y;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unsigned() {
+        assert_eq!(Point::from_unsigned(3, 4), Point::new(3, 4));
+        assert_eq!(Point::new(3, 4).to_unsigned(), Some((3, 4)));
+        assert_eq!(Point::new(-1, 4).to_unsigned(), None);
+        assert_eq!(Point::from((2, -5)), Point::new(2, -5));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let mut p = Point::new(3, -4) + Point::new(1, 2);
+        assert_eq!(p, Point::new(4, -2));
+        assert_eq!(p - Point::new(5, 5), Point::new(-1, -7));
+        assert_eq!(-p, Point::new(-4, 2));
+
+        p += Point::new(1, 1);
+        assert_eq!(p, Point::new(5, -1));
+        p -= Point::new(5, -1);
+        assert_eq!(p, Point::default());
+    }
 }
@@ -2,17 +2,207 @@
 //!
 //! Copyright 2019 Ryan Kurte
 
-/// Rectangle in 2d space
+use super::point::Point;
+use super::size::Size;
+
+/// Rectangle in 2d space, with a signed origin and unsigned size
+///
+/// Rectangles cover the area from `x` to `x + w` and `y` to `y + h`, excluding the
+/// right and bottom edges.
 #[derive(Clone, Copy, Default, Debug, PartialEq)]
 pub struct Rect {
-    pub x: usize,
-    pub y: usize,
+    pub x: isize,
+    pub y: isize,
     pub w: usize,
     pub h: usize,
 }
 
 impl Rect {
-    pub fn new(x: usize, y: usize, w: usize, h: usize) -> Rect {
+    pub fn new(x: isize, y: isize, w: usize, h: usize) -> Rect {
         Rect { x, y, w, h }
     }
+
+    /// Create a rectangle from an unsigned origin
+    pub fn from_unsigned(x: usize, y: usize, w: usize, h: usize) -> Rect {
+        Rect {
+            x: x as isize,
+            y: y as isize,
+            w,
+            h,
+        }
+    }
+
+    /// Create a rectangle from an origin and size
+    pub fn from_origin_size(origin: Point, size: Size) -> Rect {
+        Rect::new(origin.x, origin.y, size.w, size.h)
+    }
+
+    /// Fetch the origin (top left corner) of the rectangle
+    pub fn origin(&self) -> Point {
+        Point::new(self.x, self.y)
+    }
+
+    /// Fetch the size of the rectangle
+    pub fn size(&self) -> Size {
+        Size::new(self.w, self.h)
+    }
+
+    /// Right edge of the rectangle (exclusive)
+    pub fn right(&self) -> isize {
+        self.x + self.w as isize
+    }
+
+    /// Bottom edge of the rectangle (exclusive)
+    pub fn bottom(&self) -> isize {
+        self.y + self.h as isize
+    }
+
+    /// Check whether the rectangle has no area
+    pub fn is_empty(&self) -> bool {
+        self.w == 0 || self.h == 0
+    }
+
+    /// Centre of the rectangle (rounded towards the origin)
+    pub fn center(&self) -> Point {
+        Point::new(self.x + self.w as isize / 2, self.y + self.h as isize / 2)
+    }
+
+    /// Check whether a point lies within the rectangle
+    pub fn contains(&self, p: Point) -> bool {
+        p.x >= self.x && p.x < self.right() && p.y >= self.y && p.y < self.bottom()
+    }
+
+    /// Compute the overlapping area of two rectangles, returning None if they do not overlap
+    pub fn intersect(&self, o: &Rect) -> Option<Rect> {
+        let (x, y) = (self.x.max(o.x), self.y.max(o.y));
+        let (right, bottom) = (self.right().min(o.right()), self.bottom().min(o.bottom()));
+
+        if right > x && bottom > y {
+            Some(Rect::new(x, y, (right - x) as usize, (bottom - y) as usize))
+        } else {
+            None
+        }
+    }
+
+    /// Compute the smallest rectangle containing both rectangles, ignoring empty rectangles
+    pub fn union(&self, o: &Rect) -> Rect {
+        if o.is_empty() {
+            return *self;
+        }
+        if self.is_empty() {
+            return *o;
+        }
+
+        let (x, y) = (self.x.min(o.x), self.y.min(o.y));
+        let (right, bottom) = (self.right().max(o.right()), self.bottom().max(o.bottom()));
+
+        Rect::new(x, y, (right - x) as usize, (bottom - y) as usize)
+    }
+
+    /// Shrink the rectangle by the provided amounts on each side, limited to an empty rectangle
+    /// at the centre
+    pub fn inset(&self, dx: usize, dy: usize) -> Rect {
+        Rect::new(
+            self.x + dx.min(self.w / 2) as isize,
+            self.y + dy.min(self.h / 2) as isize,
+            self.w.saturating_sub(dx.saturating_mul(2)),
+            self.h.saturating_sub(dy.saturating_mul(2)),
+        )
+    }
+
+    /// Grow the rectangle by the provided amounts on each side
+    pub fn outset(&self, dx: usize, dy: usize) -> Rect {
+        Rect::new(
+            self.x - dx as isize,
+            self.y - dy as isize,
+            self.w + dx * 2,
+            self.h + dy * 2,
+        )
+    }
+
+    /// Move the rectangle by the provided offset
+    pub fn translate(&self, offset: Point) -> Rect {
+        Rect::new(self.x + offset.x, self.y + offset.y, self.w, self.h)
+    }
+
+    /// Split the rectangle into left and right sections, at the provided distance from the
+    /// left edge (limited to the rectangle width)
+    pub fn split_horizontal(&self, at: usize) -> (Rect, Rect) {
+        let at = at.min(self.w);
+
+        (
+            Rect::new(self.x, self.y, at, self.h),
+            Rect::new(self.x + at as isize, self.y, self.w - at, self.h),
+        )
+    }
+
+    /// Split the rectangle into top and bottom sections, at the provided distance from the
+    /// top edge (limited to the rectangle height)
+    pub fn split_vertical(&self, at: usize) -> (Rect, Rect) {
+        let at = at.min(self.h);
+
+        (
+            Rect::new(self.x, self.y, self.w, at),
+            Rect::new(self.x, self.y + at as isize, self.w, self.h - at),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intersect_union() {
+        let a = Rect::new(0, 0, 10, 10);
+        let b = Rect::new(-5, 5, 10, 10);
+
+        assert_eq!(a.intersect(&b), Some(Rect::new(0, 5, 5, 5)));
+        assert_eq!(a.union(&b), Rect::new(-5, 0, 15, 15));
+
+        // Touching edges do not overlap
+        assert_eq!(a.intersect(&Rect::new(10, 0, 5, 5)), None);
+        assert_eq!(a.union(&Rect::new(100, 100, 0, 0)), a);
+    }
+
+    #[test]
+    fn test_contains() {
+        let r = Rect::new(-2, 3, 4, 2);
+
+        assert!(r.contains(Point::new(-2, 3)));
+        assert!(r.contains(Point::new(1, 4)));
+        assert!(!r.contains(Point::new(2, 4)));
+        assert!(!r.contains(Point::new(0, 5)));
+        assert_eq!(r.center(), Point::new(0, 4));
+    }
+
+    #[test]
+    fn test_inset_outset() {
+        let r = Rect::new(1, 2, 10, 6);
+
+        assert_eq!(r.inset(2, 1), Rect::new(3, 3, 6, 4));
+        assert_eq!(r.inset(20, 1), Rect::new(6, 3, 0, 4));
+        assert_eq!(r.outset(2, 1), Rect::new(-1, 1, 14, 8));
+        assert_eq!(r.translate(Point::new(-3, 1)), Rect::new(-2, 3, 10, 6));
+
+        // Odd sizes collapse to empty rather than leaving the centre pixel
+        let odd = Rect::new(0, 0, 5, 7);
+        assert_eq!(odd.inset(3, 3), Rect::new(2, 3, 0, 1));
+        assert_eq!(odd.inset(3, 4), Rect::new(2, 3, 0, 0));
+        assert_eq!(odd.inset(usize::MAX, 0), Rect::new(2, 0, 0, 7));
+    }
+
+    #[test]
+    fn test_split() {
+        let r = Rect::new(0, 0, 10, 6);
+
+        assert_eq!(
+            r.split_horizontal(4),
+            (Rect::new(0, 0, 4, 6), Rect::new(4, 0, 6, 6))
+        );
+        assert_eq!(
+            r.split_vertical(8),
+            (Rect::new(0, 0, 10, 6), Rect::new(0, 6, 10, 0))
+        );
+    }
 }
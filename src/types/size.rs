@@ -0,0 +1,21 @@
+//! Size represents the dimensions of an object in 2d space
+//!
+//! Copyright 2019 Ryan Kurte
+
+/// Size in 2d space
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub struct Size {
+    pub w: usize,
+    pub h: usize,
+}
+
+impl Size {
+    pub fn new(w: usize, h: usize) -> Size {
+        Size { w, h }
+    }
+
+    /// Check whether the size has no area
+    pub fn is_empty(&self) -> bool {
+        self.w == 0 || self.h == 0
+    }
+}
@@ -1,5 +1,6 @@
 use crate::core::buffer::Buff;
 
+use crate::graphics::stroke::Stroke;
 use crate::graphics::{Graphics, Renderable};
 use crate::types::pixel::*;
 use crate::types::point::Point;
@@ -19,122 +20,108 @@ where
 {
     fn render(&mut self, graphics: &mut Graphics<Pixel>, buffer: &mut Buff<Pixel>) {
         let bounds = graphics.get_bounds();
+        let (w, h) = (bounds.w as isize, bounds.h as isize);
+        let solid = Stroke::default();
 
         // Lines
         graphics.draw_line(
             buffer,
             Point { x: 20, y: 20 },
-            Point {
-                x: bounds.w - 20,
-                y: 20,
-            },
+            Point { x: w - 20, y: 20 },
+            &solid,
             &Pixel::black(),
         );
         graphics.draw_line(
             buffer,
             Point { x: 0, y: 0 },
-            Point {
-                x: bounds.w,
-                y: bounds.h,
-            },
+            Point { x: w, y: h },
+            &solid,
             &Pixel::black(),
         );
         graphics.draw_line(
             buffer,
-            Point { x: 0, y: bounds.h },
-            Point { x: bounds.w, y: 0 },
+            Point { x: 0, y: h },
+            Point { x: w, y: 0 },
+            &solid,
             &Pixel::black(),
         );
 
         let points = [
             Point {
-                x: bounds.w / 6 * 1,
-                y: bounds.h / 8 * 2,
+                x: w / 6 * 1,
+                y: h / 8 * 2,
             },
             Point {
-                x: bounds.w / 6 * 2,
-                y: bounds.h / 8 * 1,
+                x: w / 6 * 2,
+                y: h / 8 * 1,
             },
             Point {
-                x: bounds.w / 6 * 3,
-                y: bounds.h / 8 * 2,
+                x: w / 6 * 3,
+                y: h / 8 * 2,
             },
             Point {
-                x: bounds.w / 6 * 4,
-                y: bounds.h / 8 * 1,
+                x: w / 6 * 4,
+                y: h / 8 * 1,
             },
             Point {
-                x: bounds.w / 6 * 5,
-                y: bounds.h / 8 * 2,
+                x: w / 6 * 5,
+                y: h / 8 * 2,
             },
         ];
-        graphics.draw_polyline(buffer, &points, &Pixel::black());
+        graphics.draw_polyline(buffer, &points, &solid, &Pixel::black());
 
         // Circles
-        let ellipse_r = bounds.w / 4;
+        let ellipse_r = w / 4;
+        let ellipse_size = ellipse_r as usize;
         let circles = [
             Rect {
-                x: (bounds.w - ellipse_r) / 2 - ellipse_r / 5 * 3,
-                y: (bounds.h - ellipse_r) / 2,
-                w: ellipse_r,
-                h: ellipse_r,
+                x: (w - ellipse_r) / 2 - ellipse_r / 5 * 3,
+                y: (h - ellipse_r) / 2,
+                w: ellipse_size,
+                h: ellipse_size,
             },
             Rect {
-                x: (bounds.w - ellipse_r) / 2,
-                y: (bounds.h - ellipse_r) / 2,
-                w: ellipse_r,
-                h: ellipse_r,
+                x: (w - ellipse_r) / 2,
+                y: (h - ellipse_r) / 2,
+                w: ellipse_size,
+                h: ellipse_size,
             },
             Rect {
-                x: (bounds.w - ellipse_r) / 2 + ellipse_r / 5 * 3,
-                y: (bounds.h - ellipse_r) / 2,
-                w: ellipse_r,
-                h: ellipse_r,
+                x: (w - ellipse_r) / 2 + ellipse_r / 5 * 3,
+                y: (h - ellipse_r) / 2,
+                w: ellipse_size,
+                h: ellipse_size,
             },
         ];
-        graphics.draw_ellipse(buffer, circles[0], &Pixel::red());
-        graphics.draw_ellipse(buffer, circles[1], &Pixel::green());
-        graphics.draw_ellipse(buffer, circles[2], &Pixel::blue());
+        graphics.draw_ellipse(buffer, circles[0], &solid, &Pixel::red());
+        graphics.draw_ellipse(buffer, circles[1], &solid, &Pixel::green());
+        graphics.draw_ellipse(buffer, circles[2], &solid, &Pixel::blue());
 
         // Rectangles
         graphics.draw_rect(
             buffer,
             Rect::new(
-                bounds.w / 7 * 1 - 16,
-                bounds.h / 8 * 6 - 16,
+                w / 7 * 1 - 16,
+                h / 8 * 6 - 16,
                 bounds.w / 7 * 5 + 32,
                 bounds.h / 6 + 32,
             ),
+            &solid,
             &Pixel::black(),
         );
         graphics.fill_rect(
             buffer,
-            Rect::new(
-                bounds.w / 7 * 1,
-                bounds.h / 8 * 6,
-                bounds.w / 7,
-                bounds.h / 6,
-            ),
+            Rect::new(w / 7 * 1, h / 8 * 6, bounds.w / 7, bounds.h / 6),
             &Pixel::red(),
         );
         graphics.fill_rect(
             buffer,
-            Rect::new(
-                bounds.w / 7 * 3,
-                bounds.h / 8 * 6,
-                bounds.w / 7,
-                bounds.h / 6,
-            ),
+            Rect::new(w / 7 * 3, h / 8 * 6, bounds.w / 7, bounds.h / 6),
             &Pixel::green(),
         );
         graphics.fill_rect(
             buffer,
-            Rect::new(
-                bounds.w / 7 * 5,
-                bounds.h / 8 * 6,
-                bounds.w / 7,
-                bounds.h / 6,
-            ),
+            Rect::new(w / 7 * 5, h / 8 * 6, bounds.w / 7, bounds.h / 6),
             &Pixel::blue(),
         );
     }
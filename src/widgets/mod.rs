@@ -1 +1,3 @@
 pub mod demo;
+pub mod qr;
+pub mod segment;
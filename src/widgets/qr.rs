@@ -0,0 +1,868 @@
+//! QR codes, with an encoder for byte and alphanumeric data and a widget rendering codes
+//! centred in the layer bounds
+//!
+//! Codes use the smallest version (1 to 40) that fits the data at the selected error
+//! correction level, with the mask chosen by the standard penalty rules.
+//!
+//! Copyright 2019 Ryan Kurte
+
+use std::fmt;
+
+use crate::core::buffer::Buff;
+use crate::graphics::{Graphics, Renderable};
+use crate::types::rect::Rect;
+
+/// Width of the light border required around codes, in modules
+pub const QUIET_ZONE: usize = 4;
+
+/// Characters supported by alphanumeric mode, in encoding order
+const ALPHANUMERIC: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
+
+/// Error correction codewords per block, by level (low to high) and version
+const ECC_CODEWORDS: [[u8; 41]; 4] = [
+    [
+        0, 7, 10, 15, 20, 26, 18, 20, 24, 30, 18, 20, 24, 26, 30, 22, 24, 28, 30, 28, 28, 28, 28,
+        30, 30, 26, 28, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30,
+    ],
+    [
+        0, 10, 16, 26, 18, 24, 16, 18, 22, 22, 26, 30, 22, 22, 24, 24, 28, 28, 26, 26, 26, 26, 28,
+        28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28,
+    ],
+    [
+        0, 13, 22, 18, 26, 18, 24, 18, 22, 20, 24, 28, 26, 24, 20, 30, 24, 28, 28, 26, 30, 28, 30,
+        30, 30, 30, 28, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30,
+    ],
+    [
+        0, 17, 28, 22, 16, 22, 28, 26, 26, 24, 28, 24, 28, 22, 24, 24, 30, 28, 28, 26, 28, 30, 24,
+        30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30,
+    ],
+];
+
+/// Error correction blocks, by level (low to high) and version
+const ECC_BLOCKS: [[u8; 41]; 4] = [
+    [
+        0, 1, 1, 1, 1, 1, 2, 2, 2, 2, 4, 4, 4, 4, 4, 6, 6, 6, 6, 7, 8, 8, 9, 9, 10, 12, 12, 12, 13,
+        14, 15, 16, 17, 18, 19, 19, 20, 21, 22, 24, 25,
+    ],
+    [
+        0, 1, 1, 1, 2, 2, 4, 4, 4, 5, 5, 5, 8, 9, 9, 10, 10, 11, 13, 14, 16, 17, 17, 18, 20, 21,
+        23, 25, 26, 28, 29, 31, 33, 35, 37, 38, 40, 43, 45, 47, 49,
+    ],
+    [
+        0, 1, 1, 2, 2, 4, 4, 6, 6, 8, 8, 8, 10, 12, 16, 12, 17, 16, 18, 21, 20, 23, 23, 25, 27, 29,
+        34, 34, 35, 38, 40, 43, 45, 48, 51, 53, 56, 59, 62, 65, 68,
+    ],
+    [
+        0, 1, 1, 2, 4, 4, 4, 5, 6, 8, 8, 11, 11, 16, 16, 18, 16, 19, 21, 25, 25, 25, 34, 30, 32,
+        35, 37, 40, 42, 45, 48, 51, 54, 57, 60, 63, 66, 70, 74, 77, 81,
+    ],
+];
+
+/// QR encoding errors
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Error {
+    /// Data does not fit in the largest code at the selected error correction level
+    TooLong,
+    /// Character not supported by alphanumeric mode
+    InvalidCharacter(char),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::TooLong => write!(f, "data too long for a QR code"),
+            Error::InvalidCharacter(c) => write!(f, "invalid alphanumeric character: {:?}", c),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Error correction level, with the approximate proportion of a code that can be recovered
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Ecc {
+    /// 7% recovery
+    Low,
+    /// 15% recovery
+    Medium,
+    /// 25% recovery
+    Quartile,
+    /// 30% recovery
+    High,
+}
+
+impl Ecc {
+    /// Index into the error correction tables
+    fn index(self) -> usize {
+        match self {
+            Ecc::Low => 0,
+            Ecc::Medium => 1,
+            Ecc::Quartile => 2,
+            Ecc::High => 3,
+        }
+    }
+
+    /// Level bits used in format information
+    fn format_bits(self) -> u32 {
+        match self {
+            Ecc::Low => 1,
+            Ecc::Medium => 0,
+            Ecc::Quartile => 3,
+            Ecc::High => 2,
+        }
+    }
+}
+
+/// Data encoding mode
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mode {
+    /// Arbitrary bytes, 8 bits per byte
+    Byte,
+    /// Digits, upper case letters, space and `$%*+-./:`, 5.5 bits per character
+    Alphanumeric,
+}
+
+impl Mode {
+    /// Mode indicator bits
+    fn indicator(self) -> u32 {
+        match self {
+            Mode::Byte => 0b0100,
+            Mode::Alphanumeric => 0b0010,
+        }
+    }
+
+    /// Length of the character count field for a version
+    fn count_bits(self, version: usize) -> usize {
+        let i = match version {
+            1..=9 => 0,
+            10..=26 => 1,
+            _ => 2,
+        };
+        match self {
+            Mode::Byte => [8, 16, 16][i],
+            Mode::Alphanumeric => [9, 11, 13][i],
+        }
+    }
+}
+
+/// Append the low bits of a value to a bit buffer, most significant bit first
+fn append(bits: &mut Vec<bool>, value: u32, len: usize) {
+    bits.extend((0..len).rev().map(|i| (value >> i) & 1 != 0));
+}
+
+/// Multiply two elements of GF(2^8), modulo the QR code polynomial
+fn gf_mul(x: u8, y: u8) -> u8 {
+    let mut z: u32 = 0;
+    for i in (0..8).rev() {
+        z = (z << 1) ^ ((z >> 7) * 0x11d);
+        z ^= ((y as u32 >> i) & 1) * x as u32;
+    }
+    z as u8
+}
+
+/// Reed-Solomon generator polynomial of the provided degree (without the leading term)
+fn rs_divisor(degree: usize) -> Vec<u8> {
+    let mut result = vec![0; degree];
+    result[degree - 1] = 1;
+
+    let mut root = 1;
+    for _ in 0..degree {
+        for j in 0..degree {
+            result[j] = gf_mul(result[j], root);
+            if j + 1 < degree {
+                result[j] ^= result[j + 1];
+            }
+        }
+        root = gf_mul(root, 0x02);
+    }
+
+    result
+}
+
+/// Reed-Solomon error correction codewords for a block of data
+fn rs_remainder(data: &[u8], divisor: &[u8]) -> Vec<u8> {
+    let mut result = vec![0; divisor.len()];
+
+    for b in data {
+        let factor = b ^ result.remove(0);
+        result.push(0);
+        for (r, d) in result.iter_mut().zip(divisor) {
+            *r ^= gf_mul(*d, factor);
+        }
+    }
+
+    result
+}
+
+/// Number of data and error correction bits in a version, excluding function patterns
+fn raw_modules(version: usize) -> usize {
+    let mut result = (16 * version + 128) * version + 64;
+    if version >= 2 {
+        let align = version / 7 + 2;
+        result -= (25 * align - 10) * align - 55;
+        if version >= 7 {
+            result -= 36;
+        }
+    }
+    result
+}
+
+/// Number of data codewords in a version at an error correction level
+fn data_codewords(version: usize, ecc: Ecc) -> usize {
+    let e = ecc.index();
+    raw_modules(version) / 8 - ECC_CODEWORDS[e][version] as usize * ECC_BLOCKS[e][version] as usize
+}
+
+/// Centre positions of alignment patterns (in both axes) for a version
+fn alignment_positions(version: usize) -> Vec<usize> {
+    if version == 1 {
+        return vec![];
+    }
+
+    let count = version / 7 + 2;
+    let step = match version {
+        32 => 26,
+        _ => (version * 4 + count * 2 + 1) / (count * 2 - 2) * 2,
+    };
+
+    let last = version * 4 + 10;
+    let mut result: Vec<usize> = (0..count - 1).map(|i| last - i * step).collect();
+    result.push(6);
+    result.reverse();
+    result
+}
+
+/// Encoded QR code, with dark modules set
+#[derive(Clone, Debug, PartialEq)]
+pub struct QrCode {
+    version: usize,
+    size: usize,
+    modules: Vec<bool>,
+}
+
+/// Module matrix under construction, tracking function modules that are not masked
+struct Builder {
+    size: usize,
+    modules: Vec<bool>,
+    function: Vec<bool>,
+}
+
+impl Builder {
+    fn new(version: usize) -> Self {
+        let size = version * 4 + 17;
+        let mut b = Builder {
+            size,
+            modules: vec![false; size * size],
+            function: vec![false; size * size],
+        };
+
+        // Timing patterns
+        for i in 0..size {
+            b.set_function(6, i, i % 2 == 0);
+            b.set_function(i, 6, i % 2 == 0);
+        }
+
+        // Finder patterns (with separators) in three corners
+        for &(x, y) in &[(3, 3), (size - 4, 3), (3, size - 4)] {
+            for dy in -4isize..=4 {
+                for dx in -4isize..=4 {
+                    let (xx, yy) = (x as isize + dx, y as isize + dy);
+                    if xx >= 0 && yy >= 0 && (xx as usize) < size && (yy as usize) < size {
+                        let d = dx.abs().max(dy.abs());
+                        b.set_function(xx as usize, yy as usize, d != 2 && d != 4);
+                    }
+                }
+            }
+        }
+
+        // Alignment patterns, except where they would overlap the finder patterns
+        let positions = alignment_positions(version);
+        let n = positions.len();
+        for (i, &x) in positions.iter().enumerate() {
+            for (j, &y) in positions.iter().enumerate() {
+                if (i == 0 && (j == 0 || j == n - 1)) || (i == n - 1 && j == 0) {
+                    continue;
+                }
+                for dy in -2isize..=2 {
+                    for dx in -2isize..=2 {
+                        let d = dx.abs().max(dy.abs());
+                        let (xx, yy) = ((x as isize + dx) as usize, (y as isize + dy) as usize);
+                        b.set_function(xx, yy, d != 1);
+                    }
+                }
+            }
+        }
+
+        // Reserve format information (drawn once the mask is selected)
+        b.draw_format(Ecc::Low, 0);
+
+        // Version information, for versions 7 and above
+        if version >= 7 {
+            let mut rem = version as u32;
+            for _ in 0..12 {
+                rem = (rem << 1) ^ ((rem >> 11) * 0x1f25);
+            }
+            let bits = (version as u32) << 12 | rem;
+
+            for i in 0..18 {
+                let dark = (bits >> i) & 1 != 0;
+                let (a, c) = (size - 11 + i % 3, i / 3);
+                b.set_function(a, c, dark);
+                b.set_function(c, a, dark);
+            }
+        }
+
+        b
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y * self.size + x] = dark;
+        self.function[y * self.size + x] = true;
+    }
+
+    /// Draw both copies of the format information for a level and mask
+    fn draw_format(&mut self, ecc: Ecc, mask: usize) {
+        let data = ecc.format_bits() << 3 | mask as u32;
+        let mut rem = data;
+        for _ in 0..10 {
+            rem = (rem << 1) ^ ((rem >> 9) * 0x537);
+        }
+        let bits = (data << 10 | rem) ^ 0x5412;
+        let bit = |i: usize| (bits >> i) & 1 != 0;
+        let size = self.size;
+
+        // Around the top left finder
+        for i in 0..6 {
+            self.set_function(8, i, bit(i));
+        }
+        self.set_function(8, 7, bit(6));
+        self.set_function(8, 8, bit(7));
+        self.set_function(7, 8, bit(8));
+        for i in 9..15 {
+            self.set_function(14 - i, 8, bit(i));
+        }
+
+        // Split between the top right and bottom left finders, with the fixed dark module
+        for i in 0..8 {
+            self.set_function(size - 1 - i, 8, bit(i));
+        }
+        for i in 8..15 {
+            self.set_function(8, size - 15 + i, bit(i));
+        }
+        self.set_function(8, size - 8, true);
+    }
+
+    /// Positions of data modules in placement order, in a zigzag pattern of two module
+    /// columns from the bottom right, skipping function modules
+    fn data_positions(&self) -> Vec<usize> {
+        let size = self.size;
+        let mut positions = Vec::new();
+
+        let mut right = size - 1;
+        while right >= 1 {
+            // Skip the vertical timing pattern
+            if right == 6 {
+                right = 5;
+            }
+
+            let upward = (right + 1) & 2 == 0;
+            for vert in 0..size {
+                let y = if upward { size - 1 - vert } else { vert };
+                for j in 0..2 {
+                    let i = y * size + right - j;
+                    if !self.function[i] {
+                        positions.push(i);
+                    }
+                }
+            }
+
+            if right < 2 {
+                break;
+            }
+            right -= 2;
+        }
+
+        positions
+    }
+
+    /// Place codewords in the data modules, leaving any remainder modules light
+    fn draw_codewords(&mut self, data: &[u8]) {
+        for (i, p) in self
+            .data_positions()
+            .into_iter()
+            .take(data.len() * 8)
+            .enumerate()
+        {
+            self.modules[p] = (data[i >> 3] >> (7 - (i & 7))) & 1 != 0;
+        }
+    }
+
+    /// Invert data modules selected by a mask pattern (applying a mask twice removes it)
+    fn apply_mask(&mut self, mask: usize) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let invert = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+
+                let i = y * self.size + x;
+                if invert && !self.function[i] {
+                    self.modules[i] = !self.modules[i];
+                }
+            }
+        }
+    }
+
+    /// Penalty score for the current matrix, lower scores being easier to scan
+    fn penalty(&self) -> usize {
+        let size = self.size;
+        let get = |x: usize, y: usize| self.modules[y * size + x];
+        let mut score = 0;
+
+        // Runs of five or more modules of the same colour, and finder-like patterns, in rows
+        // and columns
+        const FINDER: [bool; 11] = [
+            true, false, true, true, true, false, true, false, false, false, false,
+        ];
+        for transpose in &[false, true] {
+            let line = |i: usize, j: usize| if *transpose { get(i, j) } else { get(j, i) };
+
+            for i in 0..size {
+                let mut run = 1;
+                for j in 1..size {
+                    if line(i, j) == line(i, j - 1) {
+                        run += 1;
+                        if run == 5 {
+                            score += 3;
+                        } else if run > 5 {
+                            score += 1;
+                        }
+                    } else {
+                        run = 1;
+                    }
+                }
+
+                for j in 0..size.saturating_sub(10) {
+                    let forward = (0..11).all(|k| line(i, j + k) == FINDER[k]);
+                    let backward = (0..11).all(|k| line(i, j + k) == FINDER[10 - k]);
+                    score += 40 * (forward as usize + backward as usize);
+                }
+            }
+        }
+
+        // Two by two blocks of the same colour
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let c = get(x, y);
+                if c == get(x + 1, y) && c == get(x, y + 1) && c == get(x + 1, y + 1) {
+                    score += 3;
+                }
+            }
+        }
+
+        // Deviation from an even balance of dark and light modules, in steps of 5%
+        let dark = self.modules.iter().filter(|&&m| m).count();
+        let total = size * size;
+        let deviation = (dark * 20).abs_diff(total * 10);
+        score += deviation.div_ceil(total).saturating_sub(1) * 10;
+
+        score
+    }
+}
+
+impl QrCode {
+    /// Encode data in the provided mode and error correction level, using the smallest
+    /// version that fits
+    pub fn encode(data: &[u8], mode: Mode, ecc: Ecc) -> Result<QrCode, Error> {
+        let (version, codewords) = Self::codewords(data, mode, ecc)?;
+        Ok(Self::from_codewords(version, ecc, &codewords))
+    }
+
+    /// Encode data codewords, returning the smallest version with room for the data
+    fn codewords(data: &[u8], mode: Mode, ecc: Ecc) -> Result<(usize, Vec<u8>), Error> {
+        // Encode the payload, then find a version with room for the header
+        let mut payload = Vec::new();
+        match mode {
+            Mode::Byte => data.iter().for_each(|&b| append(&mut payload, b as u32, 8)),
+            Mode::Alphanumeric => {
+                let values = data
+                    .iter()
+                    .map(|&c| match ALPHANUMERIC.iter().position(|&a| a == c) {
+                        Some(v) => Ok(v as u32),
+                        None => Err(Error::InvalidCharacter(c as char)),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                for pair in values.chunks(2) {
+                    match pair {
+                        [a, b] => append(&mut payload, a * 45 + b, 11),
+                        _ => append(&mut payload, pair[0], 6),
+                    }
+                }
+            }
+        }
+
+        let version = (1..=40)
+            .find(|&v| {
+                let count = mode.count_bits(v);
+                data.len() < 1 << count && 4 + count + payload.len() <= data_codewords(v, ecc) * 8
+            })
+            .ok_or(Error::TooLong)?;
+
+        let mut bits = Vec::new();
+        append(&mut bits, mode.indicator(), 4);
+        append(&mut bits, data.len() as u32, mode.count_bits(version));
+        bits.extend(payload);
+
+        // Terminate and pad to the data capacity, with alternating pad bytes
+        let capacity = data_codewords(version, ecc) * 8;
+        let terminator = (capacity - bits.len()).min(4);
+        append(&mut bits, 0, terminator);
+        let padding = (8 - bits.len() % 8) % 8;
+        append(&mut bits, 0, padding);
+
+        let mut codewords: Vec<u8> = bits
+            .chunks(8)
+            .map(|c| c.iter().fold(0, |b, &bit| b << 1 | bit as u8))
+            .collect();
+        for pad in [0xec, 0x11].iter().cycle() {
+            if codewords.len() * 8 >= capacity {
+                break;
+            }
+            codewords.push(*pad);
+        }
+
+        Ok((version, codewords))
+    }
+
+    /// Encode text, using alphanumeric mode where possible and byte (UTF-8) mode otherwise
+    pub fn encode_text(text: &str, ecc: Ecc) -> Result<QrCode, Error> {
+        let mode = match text.bytes().all(|c| ALPHANUMERIC.contains(&c)) {
+            true => Mode::Alphanumeric,
+            false => Mode::Byte,
+        };
+
+        Self::encode(text.as_bytes(), mode, ecc)
+    }
+
+    /// Build a code from data codewords, adding error correction and selecting a mask
+    fn from_codewords(version: usize, ecc: Ecc, data: &[u8]) -> QrCode {
+        let mut builder = Builder::new(version);
+        builder.draw_codewords(&Self::interleave(version, ecc, data));
+
+        let mask = (0..8)
+            .min_by_key(|&m| {
+                builder.apply_mask(m);
+                builder.draw_format(ecc, m);
+                let score = builder.penalty();
+                builder.apply_mask(m);
+                score
+            })
+            .unwrap();
+
+        builder.apply_mask(mask);
+        builder.draw_format(ecc, mask);
+
+        QrCode {
+            version,
+            size: builder.size,
+            modules: builder.modules,
+        }
+    }
+
+    /// Split data into blocks, add error correction to each block and interleave the results
+    fn interleave(version: usize, ecc: Ecc, data: &[u8]) -> Vec<u8> {
+        let blocks = ECC_BLOCKS[ecc.index()][version] as usize;
+        let ecc_len = ECC_CODEWORDS[ecc.index()][version] as usize;
+        let raw = raw_modules(version) / 8;
+
+        // Later blocks are one codeword longer when the data doesn't divide evenly
+        let short_blocks = blocks - raw % blocks;
+        let short_len = raw / blocks - ecc_len;
+        let divisor = rs_divisor(ecc_len);
+
+        let mut split = Vec::with_capacity(blocks);
+        let mut offset = 0;
+        for i in 0..blocks {
+            let len = short_len + if i < short_blocks { 0 } else { 1 };
+            split.push((
+                &data[offset..offset + len],
+                rs_remainder(&data[offset..offset + len], &divisor),
+            ));
+            offset += len;
+        }
+
+        let mut result = Vec::with_capacity(raw);
+        for i in 0..=short_len {
+            for (d, _) in &split {
+                if let Some(&b) = d.get(i) {
+                    result.push(b);
+                }
+            }
+        }
+        for i in 0..ecc_len {
+            for (_, e) in &split {
+                result.push(e[i]);
+            }
+        }
+
+        result
+    }
+
+    /// Code version (1 to 40)
+    pub fn version(&self) -> usize {
+        self.version
+    }
+
+    /// Width and height of the code in modules, excluding the quiet zone
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Fetch whether a module is dark
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        self.modules[y * self.size + x]
+    }
+}
+
+/// QR code widget, drawn at the largest integer module scale that fits the code and quiet
+/// zone in the layer bounds
+pub struct QrDisplay<Pixel> {
+    code: QrCode,
+    dark: Pixel,
+    light: Pixel,
+}
+
+impl<Pixel> QrDisplay<Pixel> {
+    /// Create a QR code widget with dark and light module colours
+    pub fn new(code: QrCode, dark: Pixel, light: Pixel) -> Self {
+        QrDisplay { code, dark, light }
+    }
+
+    /// Update the displayed code
+    pub fn set_code(&mut self, code: QrCode) {
+        self.code = code;
+    }
+}
+
+impl<Pixel> Renderable<Pixel> for QrDisplay<Pixel> {
+    fn render(&mut self, graphics: &mut Graphics<Pixel>, buffer: &mut dyn Buff<Pixel>) {
+        let bounds = graphics.get_bounds();
+        let size = self.code.size();
+        let total = size + QUIET_ZONE * 2;
+
+        // Codes that don't fit are drawn at one pixel per module and clipped
+        let scale = (bounds.w.min(bounds.h) / total).max(1);
+        let x0 = (bounds.w as isize - (total * scale) as isize) / 2;
+        let y0 = (bounds.h as isize - (total * scale) as isize) / 2;
+        graphics.fill_rect(
+            buffer,
+            Rect::new(x0, y0, total * scale, total * scale),
+            &self.light,
+        );
+
+        // Draw runs of dark modules in each row
+        let offset = (QUIET_ZONE * scale) as isize;
+        for y in 0..size {
+            let mut x = 0;
+            while x < size {
+                if !self.code.get(x, y) {
+                    x += 1;
+                    continue;
+                }
+
+                let start = x;
+                while x < size && self.code.get(x, y) {
+                    x += 1;
+                }
+                let r = Rect::new(
+                    x0 + offset + (start * scale) as isize,
+                    y0 + offset + (y * scale) as isize,
+                    (x - start) * scale,
+                    scale,
+                );
+                graphics.fill_rect(buffer, r, &self.dark);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::core::buffer::Buffer;
+    use crate::types::pixel::PixelBW;
+
+    /// Format information for medium error correction, by mask
+    const FORMAT_MEDIUM: [u16; 8] = [
+        0b101010000010010,
+        0b101000100100101,
+        0b101111001111100,
+        0b101101101001011,
+        0b100010111111001,
+        0b100000011001110,
+        0b100111110010111,
+        0b100101010100000,
+    ];
+
+    /// Read both copies of the format information from a code
+    fn format(code: &QrCode) -> (u16, u16) {
+        let size = code.size();
+        let mut first = (0..6).map(|i| (8, i)).collect::<Vec<_>>();
+        first.extend(&[(8, 7), (8, 8), (7, 8)]);
+        first.extend((9..15).map(|i| (14 - i, 8)));
+
+        let mut second = (0..8).map(|i| (size - 1 - i, 8)).collect::<Vec<_>>();
+        second.extend((8..15).map(|i| (8, size - 15 + i)));
+
+        let read = |positions: &[(usize, usize)]| {
+            positions
+                .iter()
+                .enumerate()
+                .fold(0, |v, (i, &(x, y))| v | (code.get(x, y) as u16) << i)
+        };
+        (read(&first), read(&second))
+    }
+
+    #[test]
+    fn test_codewords() {
+        let (version, data) =
+            QrCode::codewords(b"HELLO WORLD", Mode::Alphanumeric, Ecc::Medium).unwrap();
+        assert_eq!(version, 1);
+        assert_eq!(
+            data,
+            vec![32, 91, 11, 120, 209, 114, 220, 77, 67, 64, 236, 17, 236, 17, 236, 17]
+        );
+
+        let ecc = &QrCode::interleave(version, Ecc::Medium, &data)[16..];
+        assert_eq!(ecc, &[196, 35, 39, 119, 235, 215, 231, 226, 93, 23]);
+    }
+
+    #[test]
+    fn test_encode() {
+        let code = QrCode::encode_text("HELLO WORLD", Ecc::Medium).unwrap();
+        assert_eq!((code.version(), code.size()), (1, 21));
+
+        // Both copies of the format information match the level and selected mask
+        let (first, second) = format(&code);
+        assert_eq!(first, second);
+        let mask = FORMAT_MEDIUM.iter().position(|&f| f == first).unwrap();
+
+        // Removing the mask recovers the codewords
+        let mut builder = Builder::new(1);
+        builder.modules = code.modules.clone();
+        builder.apply_mask(mask);
+        let bits: Vec<bool> = builder
+            .data_positions()
+            .iter()
+            .map(|&i| builder.modules[i])
+            .collect();
+        let codewords: Vec<u8> = bits
+            .chunks_exact(8)
+            .map(|c| c.iter().fold(0, |b, &bit| b << 1 | bit as u8))
+            .collect();
+
+        let (_, data) = QrCode::codewords(b"HELLO WORLD", Mode::Alphanumeric, Ecc::Medium).unwrap();
+        assert_eq!(codewords, QrCode::interleave(1, Ecc::Medium, &data));
+
+        // Finder pattern and fixed dark module
+        assert!((0..7).all(|i| code.get(i, 0) && code.get(0, i) && code.get(20 - i, 0)));
+        assert!(!code.get(1, 1) && code.get(2, 2) && !code.get(7, 7));
+        assert!(code.get(8, 13));
+    }
+
+    #[test]
+    fn test_capacity() {
+        let version = |len: usize, mode: Mode, ecc: Ecc| {
+            QrCode::codewords(&vec![b'A'; len], mode, ecc).map(|(v, _)| v)
+        };
+
+        assert_eq!(version(17, Mode::Byte, Ecc::Low), Ok(1));
+        assert_eq!(version(18, Mode::Byte, Ecc::Low), Ok(2));
+        assert_eq!(version(7, Mode::Byte, Ecc::High), Ok(1));
+        assert_eq!(version(25, Mode::Alphanumeric, Ecc::Low), Ok(1));
+        assert_eq!(version(26, Mode::Alphanumeric, Ecc::Low), Ok(2));
+        assert_eq!(version(2953, Mode::Byte, Ecc::Low), Ok(40));
+        assert_eq!(version(2954, Mode::Byte, Ecc::Low), Err(Error::TooLong));
+        assert_eq!(version(1273, Mode::Byte, Ecc::High), Ok(40));
+        assert_eq!(version(1274, Mode::Byte, Ecc::High), Err(Error::TooLong));
+
+        assert_eq!(
+            QrCode::encode(b"Hello", Mode::Alphanumeric, Ecc::Low),
+            Err(Error::InvalidCharacter('e'))
+        );
+    }
+
+    #[test]
+    fn test_version_info() {
+        let code = QrCode::encode(&[0x55; 150], Mode::Byte, Ecc::Low).unwrap();
+        assert_eq!((code.version(), code.size()), (7, 45));
+
+        let read = |transpose: bool| {
+            (0..18).fold(0u32, |v, i| {
+                let (a, b) = (code.size() - 11 + i % 3, i / 3);
+                let dark = if transpose {
+                    code.get(b, a)
+                } else {
+                    code.get(a, b)
+                };
+                v | (dark as u32) << i
+            })
+        };
+        assert_eq!(read(false), 0x07c94);
+        assert_eq!(read(true), 0x07c94);
+
+        assert_eq!(alignment_positions(2), vec![6, 18]);
+        assert_eq!(alignment_positions(7), vec![6, 22, 38]);
+        assert_eq!(alignment_positions(32), vec![6, 34, 60, 86, 112, 138]);
+        assert_eq!(alignment_positions(36), vec![6, 24, 50, 76, 102, 128, 154]);
+    }
+
+    #[test]
+    fn test_qr_display() {
+        let code = QrCode::encode_text("HELLO WORLD", Ecc::Medium).unwrap();
+
+        for &(size, scale, offset) in &[(32, 1, 1), (64, 2, 3)] {
+            let mut data = vec![0u8; size * size / 8];
+            let mut buffer = Buffer::<PixelBW>::new(size, size, 0, 0, &mut data);
+            let mut graphics = Graphics::new(0, 0, size, size);
+            graphics.fill_rect(&mut buffer, Rect::new(0, 0, size, size), &true);
+
+            let mut display = QrDisplay::new(code.clone(), true, false);
+            display.render(&mut graphics, &mut buffer);
+
+            // Modules are drawn centred within a light quiet zone, leaving the rest of the
+            // bounds untouched
+            let total = (21 + QUIET_ZONE * 2) * scale;
+            for y in 0..size {
+                for x in 0..size {
+                    let (cx, cy) = (x as isize - offset, y as isize - offset);
+                    let inside = |v: isize| v >= 0 && v < total as isize;
+                    let quiet = (QUIET_ZONE * scale) as isize;
+
+                    let expected = match (inside(cx) && inside(cy), cx - quiet, cy - quiet) {
+                        (false, _, _) => true,
+                        (true, mx, my)
+                            if mx >= 0
+                                && my >= 0
+                                && mx < 21 * scale as isize
+                                && my < 21 * scale as isize =>
+                        {
+                            code.get(mx as usize / scale, my as usize / scale)
+                        }
+                        _ => false,
+                    };
+                    assert_eq!(buffer.get(x, y), expected, "pixel ({}, {})", x, y);
+                }
+            }
+        }
+    }
+}
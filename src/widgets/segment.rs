@@ -0,0 +1,496 @@
+//! Segment displays render digits styled like seven or fourteen segment displays, with
+//! decimal points, colons and minus signs, at any size and pixel format.
+//!
+//! Digits are drawn as filled segment polygons, optionally with unlit segments shown in
+//! a dim colour.
+//!
+//! Copyright 2019 Ryan Kurte
+
+use crate::core::buffer::Buff;
+use crate::graphics::trig::isqrt;
+use crate::graphics::{FillRule, Graphics, Renderable};
+use crate::types::point::Point;
+use crate::types::rect::Rect;
+
+// Segments, clockwise from the top then the middle (G1 and G2 are the left and right
+// halves of the middle segment on fourteen segment displays), followed by the fourteen
+// segment diagonals (H, J, K, M) and centre verticals (I, L)
+const A: u16 = 1 << 0;
+const B: u16 = 1 << 1;
+const C: u16 = 1 << 2;
+const D: u16 = 1 << 3;
+const E: u16 = 1 << 4;
+const F: u16 = 1 << 5;
+const G1: u16 = 1 << 6;
+const G2: u16 = 1 << 7;
+const H: u16 = 1 << 8;
+const I: u16 = 1 << 9;
+const J: u16 = 1 << 10;
+const K: u16 = 1 << 11;
+const L: u16 = 1 << 12;
+const M: u16 = 1 << 13;
+
+/// Middle segment on seven segment displays
+const G: u16 = G1 | G2;
+
+/// Segment display style
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Style {
+    /// Seven segments, showing digits, hexadecimal and a few other letters
+    Seven,
+    /// Fourteen segments (with diagonals and centre verticals), showing digits and letters
+    Fourteen,
+}
+
+impl Style {
+    /// Segments in the display
+    fn all(&self) -> u16 {
+        match self {
+            Style::Seven => A | B | C | D | E | F | G,
+            Style::Fourteen => 0x3fff,
+        }
+    }
+
+    /// Lit segments for a character (case insensitive), if it can be displayed
+    fn segments(&self, c: char) -> Option<u16> {
+        let s = match (self, c.to_ascii_uppercase()) {
+            (_, ' ') => 0,
+            (_, '-') => G,
+            (_, '_') => D,
+            (_, '1') => B | C,
+            (_, '2') => A | B | D | E | G,
+            (_, '3') => A | B | C | D | G,
+            (_, '4') => B | C | F | G,
+            (_, '5') | (_, 'S') => A | C | D | F | G,
+            (_, '6') => A | C | D | E | F | G,
+            (_, '7') => A | B | C,
+            (_, '8') => A | B | C | D | E | F | G,
+            (_, '9') => A | B | C | D | F | G,
+            (_, 'A') => A | B | C | E | F | G,
+            (_, 'C') => A | D | E | F,
+            (_, 'E') => A | D | E | F | G,
+            (_, 'F') => A | E | F | G,
+            (_, 'H') => B | C | E | F | G,
+            (_, 'J') => B | C | D | E,
+            (_, 'L') => D | E | F,
+            (_, 'O') => A | B | C | D | E | F,
+            (_, 'P') => A | B | E | F | G,
+            (_, 'U') => B | C | D | E | F,
+
+            (Style::Seven, '0') => A | B | C | D | E | F,
+            (Style::Seven, 'B') => C | D | E | F | G,
+            (Style::Seven, 'D') => B | C | D | E | G,
+            (Style::Seven, 'G') => A | C | D | E | F,
+            (Style::Seven, 'I') => E | F,
+            (Style::Seven, 'N') => C | E | G,
+            (Style::Seven, 'R') => E | G,
+            (Style::Seven, 'T') => D | E | F | G,
+            (Style::Seven, 'Y') => B | C | D | F | G,
+
+            (Style::Fourteen, '0') => A | B | C | D | E | F | J | K,
+            (Style::Fourteen, 'B') => A | B | C | D | G2 | I | L,
+            (Style::Fourteen, 'D') => A | B | C | D | I | L,
+            (Style::Fourteen, 'G') => A | C | D | E | F | G2,
+            (Style::Fourteen, 'I') => A | D | I | L,
+            (Style::Fourteen, 'K') => E | F | G1 | J | M,
+            (Style::Fourteen, 'M') => B | C | E | F | H | J,
+            (Style::Fourteen, 'N') => B | C | E | F | H | M,
+            (Style::Fourteen, 'Q') => A | B | C | D | E | F | M,
+            (Style::Fourteen, 'R') => A | B | E | F | G | M,
+            (Style::Fourteen, 'T') => A | I | L,
+            (Style::Fourteen, 'V') => E | F | J | K,
+            (Style::Fourteen, 'W') => B | C | E | F | K | M,
+            (Style::Fourteen, 'X') => H | J | K | M,
+            (Style::Fourteen, 'Y') => H | J | L,
+            (Style::Fourteen, 'Z') => A | D | J | K,
+            (Style::Fourteen, '+') => G | I | L,
+            (Style::Fourteen, '/') => J | K,
+            (Style::Fourteen, '*') => G | H | I | J | K | L | M,
+
+            _ => return None,
+        };
+
+        Some(s)
+    }
+}
+
+/// Display cell, laid out from a character string
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Cell {
+    /// Digit with lit segments and decimal point state
+    Digit(u16, bool),
+    /// Colon separator
+    Colon,
+}
+
+/// Segment display layout, with sizes in pixels
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Segments {
+    /// Display style
+    pub style: Style,
+    /// Digit width
+    pub width: usize,
+    /// Digit height
+    pub height: usize,
+    /// Segment thickness
+    pub thickness: usize,
+    /// Space after each digit, containing the decimal point
+    ///
+    /// Spacing narrower than the segment thickness lets the decimal point overlap the
+    /// following cell.
+    pub spacing: usize,
+}
+
+impl Segments {
+    /// Create a layout for digits of the provided height, with proportional width, segment
+    /// thickness and spacing
+    pub fn new(style: Style, height: usize) -> Self {
+        // Fourteen segment digits are wider with thinner segments, leaving room for diagonals
+        let (width, thickness) = match style {
+            Style::Seven => (height / 2, height / 8),
+            Style::Fourteen => (height * 3 / 4, height / 12),
+        };
+        let thickness = thickness.max(1);
+
+        Segments {
+            style,
+            width: width.max(3),
+            height: height.max(5),
+            thickness,
+            spacing: thickness * 2,
+        }
+    }
+
+    /// Lay out display cells for a string, with decimal points attached to the preceding
+    /// digit and characters that can't be displayed shown as blank digits
+    fn cells(&self, text: &str) -> Vec<Cell> {
+        let mut cells = Vec::new();
+
+        for c in text.chars() {
+            match (c, cells.last_mut()) {
+                ('.', Some(Cell::Digit(_, dp))) if !*dp => *dp = true,
+                ('.', _) => cells.push(Cell::Digit(0, true)),
+                (':', _) => cells.push(Cell::Colon),
+                (c, _) => cells.push(Cell::Digit(self.style.segments(c).unwrap_or(0), false)),
+            }
+        }
+
+        cells
+    }
+
+    /// Width of a display cell in pixels, including the following space
+    fn cell_width(&self, cell: &Cell) -> usize {
+        match cell {
+            Cell::Digit(..) => self.width + self.spacing,
+            Cell::Colon => self.thickness + self.spacing,
+        }
+    }
+
+    /// Measure the width of a string in pixels
+    pub fn text_width(&self, text: &str) -> usize {
+        self.cells(text).iter().map(|c| self.cell_width(c)).sum()
+    }
+
+    /// Polygon for a segment of a digit with its top left corner at the provided position
+    fn polygon(&self, pos: Point, segment: u16) -> Vec<Point> {
+        let (w, h, t) = (
+            self.width as isize,
+            self.height as isize,
+            self.thickness as isize,
+        );
+
+        // Segment centre lines, with the thickness split unevenly for even widths
+        let (before, after) = ((t - 1) / 2, t / 2);
+        let left = pos.x + before;
+        let right = pos.x + w - 1 - after;
+        let centre = pos.x + (w - 1) / 2;
+        let top = pos.y + before;
+        let middle = pos.y + (h - 1) / 2;
+        let bottom = pos.y + h - 1 - after;
+
+        // Gap between segment ends
+        let gap = (t + 3) / 4;
+
+        // Bevelled horizontal and vertical segments between two points on a centre line
+        let horizontal = |x0: isize, x1: isize, y: isize| {
+            let (x0, x1) = (x0 + gap, x1 - gap);
+            vec![
+                Point::new(x0, y),
+                Point::new(x0 + before, y - before),
+                Point::new(x1 - before, y - before),
+                Point::new(x1, y),
+                Point::new(x1 - after, y + after),
+                Point::new(x0 + after, y + after),
+            ]
+        };
+        let vertical = |x: isize, y0: isize, y1: isize| {
+            let (y0, y1) = (y0 + gap, y1 - gap);
+            vec![
+                Point::new(x, y0),
+                Point::new(x + after, y0 + after),
+                Point::new(x + after, y1 - after),
+                Point::new(x, y1),
+                Point::new(x - before, y1 - before),
+                Point::new(x - before, y0 + before),
+            ]
+        };
+
+        // Diagonals are bands running corner to corner across a quarter of the digit,
+        // inset to clear the surrounding segments and one segment thick
+        let diagonal = |x0: isize, y0: isize, x1: isize, y1: isize| {
+            let sx = (x1 - x0).signum();
+            let (x0, x1) = match sx {
+                1 => (x0 + after + 1 + gap, x1 - before - 1 - gap),
+                _ => (x0 - before - 1 - gap, x1 + after + 1 + gap),
+            };
+            let (y0, y1) = (y0 + after + 1 + gap, y1 - before - 1 - gap);
+
+            // Band edges are offset either side of the diagonal, with the offsets along the
+            // box edges lengthened for the band slope
+            let (w, h) = ((x1 - x0).abs().max(1), (y1 - y0).max(1));
+            let len = isqrt(w * w + h * h);
+            let (dx, dy) = ((t * len / (2 * h)).min(w), (t * len / (2 * w)).min(h));
+            vec![
+                Point::new(x0, y0),
+                Point::new(x0 + sx * dx, y0),
+                Point::new(x1, y1 - dy),
+                Point::new(x1, y1),
+                Point::new(x1 - sx * dx, y1),
+                Point::new(x0, y0 + dy),
+            ]
+        };
+
+        match segment {
+            A => horizontal(left, right, top),
+            B => vertical(right, top, middle),
+            C => vertical(right, middle, bottom),
+            D => horizontal(left, right, bottom),
+            E => vertical(left, middle, bottom),
+            F => vertical(left, top, middle),
+            G1 if self.style == Style::Seven => horizontal(left, right, middle),
+            G1 => horizontal(left, centre, middle),
+            G2 => horizontal(centre, right, middle),
+            H => diagonal(left, top, centre, middle),
+            I => vertical(centre, top, middle),
+            J => diagonal(right, top, centre, middle),
+            K => diagonal(centre, middle, left, bottom),
+            L => vertical(centre, middle, bottom),
+            M => diagonal(centre, middle, right, bottom),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Draws a string with the top left corner of the first digit at the provided position,
+    /// with unlit segments and decimal points drawn in the dim colour if provided
+    pub fn draw<Pixel>(
+        &self,
+        graphics: &Graphics<Pixel>,
+        buf: &mut dyn Buff<Pixel>,
+        pos: Point,
+        text: &str,
+        lit: &Pixel,
+        dim: Option<&Pixel>,
+    ) {
+        let t = self.thickness;
+        let mut x = pos.x;
+
+        for cell in self.cells(text) {
+            match cell {
+                Cell::Digit(segments, dp) => {
+                    for i in 0..14 {
+                        let s = 1 << i;
+                        // The middle segment is drawn whole on seven segment displays
+                        if s & self.style.all() == 0 || (self.style == Style::Seven && s == G2) {
+                            continue;
+                        }
+
+                        let p = match (segments & s != 0, dim) {
+                            (true, _) => lit,
+                            (false, Some(d)) => d,
+                            (false, None) => continue,
+                        };
+                        let polygon = self.polygon(Point::new(x, pos.y), s);
+                        graphics.fill_polygon(buf, &polygon, FillRule::NonZero, p);
+                    }
+
+                    // Decimal points are centred in the space after the digit
+                    let r = Rect::new(
+                        x + (self.width + self.spacing.saturating_sub(t) / 2) as isize,
+                        pos.y + self.height.saturating_sub(t) as isize,
+                        t,
+                        t,
+                    );
+                    match (dp, dim) {
+                        (true, _) => graphics.fill_rect(buf, r, lit),
+                        (false, Some(d)) => graphics.fill_rect(buf, r, d),
+                        (false, None) => (),
+                    }
+                }
+                Cell::Colon => {
+                    for y in &[self.height / 3, self.height * 2 / 3] {
+                        let r = Rect::new(x, pos.y + y.saturating_sub(t / 2) as isize, t, t);
+                        graphics.fill_rect(buf, r, lit);
+                    }
+                }
+            }
+
+            x += self.cell_width(&cell) as isize;
+        }
+    }
+}
+
+/// Segment display widget, showing right aligned text with digits sized to fit the
+/// layer bounds
+pub struct SegmentDisplay<Pixel> {
+    style: Style,
+    text: String,
+    lit: Pixel,
+    dim: Option<Pixel>,
+}
+
+impl<Pixel> SegmentDisplay<Pixel> {
+    /// Create a segment display with lit and (optional) dim unlit segment colours
+    pub fn new(style: Style, text: &str, lit: Pixel, dim: Option<Pixel>) -> Self {
+        SegmentDisplay {
+            style,
+            text: text.to_owned(),
+            lit,
+            dim,
+        }
+    }
+
+    /// Update the displayed text
+    pub fn set_text(&mut self, text: &str) {
+        self.text.clear();
+        self.text.push_str(text);
+    }
+}
+
+impl<Pixel> Renderable<Pixel> for SegmentDisplay<Pixel> {
+    fn render(&mut self, graphics: &mut Graphics<Pixel>, buffer: &mut dyn Buff<Pixel>) {
+        let bounds = graphics.get_bounds();
+
+        // Use the tallest digits that fit the text within the bounds
+        let mut segments = Segments::new(self.style, bounds.h);
+        let width = segments.text_width(&self.text);
+        if width > bounds.w {
+            let mut height = bounds.h * bounds.w / width + 1;
+            while height > 5 && segments.text_width(&self.text) > bounds.w {
+                height -= 1;
+                segments = Segments::new(self.style, height);
+            }
+        }
+
+        let pos = Point::new(
+            bounds.w as isize - segments.text_width(&self.text) as isize,
+            (bounds.h as isize - segments.height as isize) / 2,
+        );
+        segments.draw(
+            graphics,
+            buffer,
+            pos,
+            &self.text,
+            &self.lit,
+            self.dim.as_ref(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::core::buffer::Buffer;
+    use crate::graphics::test_utils::{bw_buffer, render, H, W};
+    use crate::types::pixel::PixelG8;
+
+    #[test]
+    fn test_cells() {
+        let segments = Segments::new(Style::Seven, 8);
+        assert_eq!(
+            segments.cells("-1..2:x"),
+            vec![
+                Cell::Digit(G, false),
+                Cell::Digit(B | C, true),
+                Cell::Digit(0, true),
+                Cell::Digit(A | B | D | E | G, false),
+                Cell::Colon,
+                Cell::Digit(0, false),
+            ]
+        );
+        assert_eq!(segments.text_width("12:34"), 4 * 6 + 3);
+    }
+
+    #[test]
+    fn test_draw_segments() {
+        let mut data = [0u8; W * H / 8];
+        let mut buffer = bw_buffer(&mut data);
+        let graphics = Graphics::new(0, 0, W, H);
+
+        let segments = Segments::new(Style::Seven, 8);
+        segments.draw(
+            &graphics,
+            &mut buffer,
+            Point::new(0, 0),
+            "-4.2",
+            &true,
+            None,
+        );
+
+        let expected = [
+            ".............##.",
+            "......#..#.....#",
+            "......#..#.....#",
+            ".##....##....##.",
+            ".........#..#...",
+            ".........#..#...",
+            ".........#..#...",
+            "..........#..##.",
+        ];
+        assert_eq!(render(&buffer), expected);
+
+        // Layouts with spacing narrower than the thickness place decimal points after the digit
+        let mut data = [0u8; W * H / 8];
+        let mut buffer = bw_buffer(&mut data);
+        let segments = Segments {
+            thickness: 2,
+            spacing: 0,
+            ..segments
+        };
+        segments.draw(&graphics, &mut buffer, Point::new(0, 0), ".", &true, None);
+
+        let mut expected = vec!["................"; H];
+        expected[6] = "....##..........";
+        expected[7] = "....##..........";
+        assert_eq!(render(&buffer), expected);
+    }
+
+    #[test]
+    fn test_segment_display() {
+        let draw = |style: Style, text: &str, dim: u8| {
+            let mut data = [0u8; W * H];
+            let mut buffer = Buffer::<PixelG8>::new(W, H, 0, 0, &mut data);
+            let mut graphics = Graphics::new(0, 0, W, H);
+
+            let mut display = SegmentDisplay::new(style, "", PixelG8(255), Some(PixelG8(dim)));
+            display.set_text(text);
+            display.render(&mut graphics, &mut buffer);
+            data
+        };
+
+        for &style in &[Style::Seven, Style::Fourteen] {
+            // Unlit segments and decimal points are drawn dim, covering the same area as
+            // fully lit segments
+            let one = draw(style, "1", 64);
+            let all = draw(style, " ", 255);
+
+            assert!(one.contains(&255) && one.contains(&64));
+            for (a, b) in one.iter().zip(all.iter()) {
+                assert_eq!(*a != 0, *b != 0);
+            }
+
+            // Digits are right aligned
+            assert!((0..H).all(|y| all[y * W] == 0));
+        }
+    }
+}